@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 2022 days live directly in src/ and data/, later years in src/yYYYY/ and data/YYYY/.
+// the crate gets it as `LEGACY_YEAR` too, see `days_registry`
+const LEGACY_YEAR: u16 = 2022;
+
+// (year, day, part, flavour) cases that take minutes without optimisations,
+// they're still generated but need `cargo test -- --ignored`
+const SLOW: &[(u16, u8, u8, &str)] = &[];
+
+fn main() {
+    println!("cargo:rerun-if-changed=data");
+    println!("cargo:rerun-if-changed=src");
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("days.rs"), days_registry()).unwrap();
+    fs::write(Path::new(&out_dir).join("data_tests.rs"), data_tests()).unwrap();
+}
+
+// "dayNN" + suffix -> NN
+fn parse_day(name: &str, suffix: &str) -> Option<u8> {
+    name.strip_prefix("day")?.strip_suffix(suffix)?.parse().ok()
+}
+
+// "yYYYY" -> YYYY for src/, "YYYY" for data/
+fn parse_year(name: &str, prefix: &str) -> Option<u16> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+fn dir_names(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("can't list {}: {e}", dir.display()))
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+// a module for every src/dayNN.rs and src/yYYYY/dayNN.rs and YEARS listing their `DAY`s,
+// so a new day only needs its file
+fn days_registry() -> String {
+    let src = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+
+    let mut years: BTreeMap<u16, Vec<(u8, PathBuf)>> = BTreeMap::new();
+    for name in dir_names(&src) {
+        if let Some(day) = parse_day(&name, ".rs") {
+            years
+                .entry(LEGACY_YEAR)
+                .or_default()
+                .push((day, src.join(&name)));
+        } else if let Some(year) = parse_year(&name, "y") {
+            assert_ne!(
+                year, LEGACY_YEAR,
+                "{LEGACY_YEAR} days go directly into src/"
+            );
+            for day_name in dir_names(&src.join(&name)) {
+                if let Some(day) = parse_day(&day_name, ".rs") {
+                    let path = src.join(&name).join(&day_name);
+                    years.entry(year).or_default().push((day, path));
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// the year the crate started with, its days and data don't go into a year dir and \
+         it's\n// the year you get without asking for one\npub const LEGACY_YEAR: u16 = \
+         {LEGACY_YEAR};\n"
+    )
+    .unwrap();
+    for (year, days) in &mut years {
+        days.sort_unstable();
+
+        let indent = if *year == LEGACY_YEAR {
+            ""
+        } else {
+            writeln!(out, "mod y{year} {{").unwrap();
+            "    "
+        };
+        for (day, path) in days.iter() {
+            writeln!(
+                out,
+                "{indent}#[path = {:?}]\n{indent}pub(crate) mod day{day:02};",
+                path.display().to_string()
+            )
+            .unwrap();
+        }
+        if *year != LEGACY_YEAR {
+            writeln!(out, "}}").unwrap();
+        }
+    }
+
+    writeln!(out, "\n// every year with its implemented days, in order").unwrap();
+    writeln!(out, "pub const YEARS: [Year; {}] = [", years.len()).unwrap();
+    for (year, days) in &years {
+        let module = if *year == LEGACY_YEAR {
+            String::new()
+        } else {
+            format!("y{year}::")
+        };
+        writeln!(
+            out,
+            "    Year {{\n        number: {year},\n        days: &["
+        )
+        .unwrap();
+        for (day, _) in days {
+            writeln!(out, "            {module}day{day:02}::DAY,").unwrap();
+        }
+        writeln!(out, "        ],\n    }},").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    out
+}
+
+// (year, day, dir) for data/dayNN and data/YYYY/dayNN, data/2022/dayNN wins over data/dayNN
+fn data_dirs() -> Vec<(u16, u8, PathBuf)> {
+    let data = Path::new("data");
+
+    let mut dirs = BTreeMap::new();
+    for name in dir_names(data) {
+        if let Some(day) = parse_day(&name, "") {
+            dirs.entry((LEGACY_YEAR, day))
+                .or_insert_with(|| data.join(&name));
+        } else if let Some(year) = parse_year(&name, "") {
+            for day_name in dir_names(&data.join(&name)) {
+                if let Some(day) = parse_day(&day_name, "") {
+                    dirs.insert((year, day), data.join(&name).join(&day_name));
+                }
+            }
+        }
+    }
+
+    dirs.into_iter()
+        .map(|((year, day), dir)| (year, day, dir))
+        .collect()
+}
+
+// one test per (year, day, part, flavour) for every input_FLAVOUR.txt in a day's data dir
+// that has a recorded answer in answer_FLAVOUR.txt next to it
+fn data_tests() -> String {
+    let mut cases = vec![];
+    for (year, day, path) in data_dirs() {
+        for name in dir_names(&path) {
+            let Some(flavour) = name
+                .strip_prefix("input_")
+                .and_then(|n| n.strip_suffix(".txt"))
+            else {
+                continue;
+            };
+
+            for part in recorded_parts(&path.join(format!("answer_{flavour}.txt"))) {
+                cases.push((year, day, part, flavour.to_string()));
+            }
+        }
+    }
+    cases.sort();
+
+    let mut out = String::new();
+    for (year, day, part, flavour) in cases {
+        let ident = flavour
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let day_part = if part == 1 { "First" } else { "Second" };
+        // 2022 tests keep their names from before there were other years
+        let year_prefix = if year == LEGACY_YEAR {
+            String::new()
+        } else {
+            format!("y{year}_")
+        };
+
+        writeln!(out, "#[test]").unwrap();
+        if SLOW.contains(&(year, day, part, flavour.as_str())) {
+            writeln!(out, "#[ignore = \"slow without optimisations\"]").unwrap();
+        }
+        writeln!(
+            out,
+            "fn {year_prefix}day{day:02}_part{part}_{ident}() {{\n    \
+             check({year}, {day}, DayPart::{day_part}, {flavour:?});\n}}\n"
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+// part numbers with a "part N:" header in the answers file, none if it's missing;
+// "part N: known missing, ..." lines don't end with a colon, so they get no test
+fn recorded_parts(answers_path: &Path) -> Vec<u8> {
+    fs::read_to_string(answers_path)
+        .map(|s| {
+            s.lines()
+                .filter_map(|l| l.strip_prefix("part ")?.strip_suffix(':')?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,305 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use adventofcode2022::{DayPart, Year};
+use color_eyre::eyre::{eyre, WrapErr};
+use itertools::{EitherOrBoth, Itertools};
+
+use crate::runner::{self, DaySelection, Input, Job};
+
+// answer_FLAVOUR.txt sits next to input_FLAVOUR.txt in the day's data dir and looks like
+//
+//   part 1:
+//   1651
+//   part 2:
+//   1707
+//
+// either part can be missing, answers can span several lines (day 10).
+// a part the solver can't do for this input is marked on its header line instead, so
+// `check` can list it rather than it going unnoticed:
+//
+//   part 2: known missing, the cube folding only knows the real input's net
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct Answers {
+    part1: Option<Recorded>,
+    part2: Option<Recorded>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Recorded {
+    Answer(String),
+    // with the reason why
+    KnownMissing(String),
+}
+
+impl Answers {
+    fn parse(s: &str) -> color_eyre::Result<Self> {
+        let mut answers = Answers::default();
+        let mut current: Option<(DayPart, Vec<&str>)> = None;
+
+        for line in s.lines() {
+            if let Some((part_s, rest)) = line.strip_prefix("part ").and_then(|l| l.split_once(':'))
+            {
+                if let Some((part, lines)) = current.take() {
+                    answers.set(part, Recorded::Answer(lines.join("\n")))?;
+                }
+                if rest.is_empty() {
+                    current = Some((part_s.parse()?, vec![]));
+                } else if let Some(reason) = rest.strip_prefix(" known missing, ") {
+                    answers.set(part_s.parse()?, Recorded::KnownMissing(reason.to_string()))?;
+                } else {
+                    return Err(eyre!(
+                        "expected \"part N:\" or \"part N: known missing, REASON\", got {line:?}"
+                    ));
+                }
+            } else if let Some((_, lines)) = &mut current {
+                lines.push(line);
+            } else {
+                return Err(eyre!("expected a \"part N:\" header, got {line:?}"));
+            }
+        }
+        if let Some((part, lines)) = current {
+            answers.set(part, Recorded::Answer(lines.join("\n")))?;
+        }
+
+        Ok(answers)
+    }
+
+    fn set(&mut self, part: DayPart, answer: Recorded) -> color_eyre::Result<()> {
+        let slot = match part {
+            DayPart::First => &mut self.part1,
+            DayPart::Second => &mut self.part2,
+        };
+        if slot.is_some() {
+            return Err(eyre!("duplicate answer for part {part}"));
+        }
+        *slot = Some(answer);
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, part: DayPart) -> Option<&Recorded> {
+        match part {
+            DayPart::First => self.part1.as_ref(),
+            DayPart::Second => self.part2.as_ref(),
+        }
+    }
+}
+
+fn answers_path(year: u16, day: u8, flavour: Option<&str>) -> PathBuf {
+    let dir = runner::day_dir(Path::new("data"), year, day);
+    if let Some(flavour) = flavour {
+        dir.join(format!("answer_{flavour}.txt"))
+    } else {
+        dir.join("answer.txt")
+    }
+}
+
+// a missing file means nobody recorded answers for this input yet
+pub(crate) fn load(
+    year: u16,
+    day: u8,
+    flavour: Option<&str>,
+) -> color_eyre::Result<Option<Answers>> {
+    let path = answers_path(year, day, flavour);
+    match fs::read_to_string(&path) {
+        Ok(s) => Answers::parse(&s)
+            .wrap_err_with(|| format!("malformed answers at {}", path.display()))
+            .map(Some),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).wrap_err_with(|| format!("can't read answers at {}", path.display())),
+    }
+}
+
+// flavours of a day that have an answers file, None is the main input
+pub(crate) fn flavours(year: u16, day: u8) -> color_eyre::Result<Vec<Option<String>>> {
+    let dir = runner::day_dir(Path::new("data"), year, day);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e).wrap_err_with(|| format!("can't list {}", dir.display())),
+    };
+
+    let mut flavours = vec![];
+    for entry in entries {
+        let name = entry?.file_name();
+        let name = name.to_string_lossy();
+        if name == "answer.txt" {
+            flavours.push(None);
+        } else if let Some(flavour) = name
+            .strip_prefix("answer_")
+            .and_then(|n| n.strip_suffix(".txt"))
+        {
+            flavours.push(Some(flavour.to_string()));
+        }
+    }
+    flavours.sort();
+
+    Ok(flavours)
+}
+
+fn diff(expected: &str, got: &str) -> String {
+    expected
+        .lines()
+        .zip_longest(got.lines())
+        .flat_map(|lines| match lines {
+            EitherOrBoth::Both(e, g) if e == g => vec![format!("  {e}")],
+            EitherOrBoth::Both(e, g) => vec![format!("- {e}"), format!("+ {g}")],
+            EitherOrBoth::Left(e) => vec![format!("- {e}")],
+            EitherOrBoth::Right(g) => vec![format!("+ {g}")],
+        })
+        .join("\n")
+}
+
+// runs every solver that has a recorded answer and returns the number of failures,
+// known missing answers are listed but don't count as failures
+pub(crate) fn check(
+    year: &Year,
+    selection: &DaySelection,
+    flavour: Option<&str>,
+    timeout: Option<Duration>,
+) -> color_eyre::Result<usize> {
+    let mut checks: Vec<(Job, String)> = vec![];
+    let mut missing: Vec<(Job, String)> = vec![];
+
+    for day in year.days.iter().filter(|d| selection.contains(d.number)) {
+        let day_flavours = if let Some(flavour) = flavour {
+            vec![Some(flavour.to_string())]
+        } else {
+            flavours(year.number, day.number)?
+        };
+
+        for day_flavour in day_flavours {
+            let Some(answers) = load(year.number, day.number, day_flavour.as_deref())? else {
+                continue;
+            };
+            for part in DayPart::BOTH {
+                let job = Job {
+                    year: year.number,
+                    day: day.number,
+                    part,
+                    input: Input::Data(day_flavour.clone()),
+                };
+                match answers.get(part) {
+                    Some(Recorded::Answer(expected)) => checks.push((job, expected.clone())),
+                    Some(Recorded::KnownMissing(reason)) => missing.push((job, reason.clone())),
+                    None => (),
+                }
+            }
+        }
+    }
+
+    let jobs = checks
+        .iter()
+        .map(|(job, _)| job.clone())
+        .collect::<Vec<_>>();
+    let results = runner::run_all(year, &jobs, timeout)?;
+
+    let mut failures = 0;
+    for ((job, expected), result) in checks.iter().zip(results) {
+        let name = format!("day {} part {} ({})", job.day, job.part, job.input);
+
+        match result.outcome {
+            // answer files hold the rendered answer, so that's what gets compared
+            Ok(got) if got.to_string() == *expected => {
+                println!("ok       {name} in {:.2?}", result.elapsed);
+            }
+            Ok(got) => {
+                failures += 1;
+                println!("MISMATCH {name}\n{}", diff(expected, &got.to_string()));
+            }
+            Err(e) => {
+                failures += 1;
+                println!("FAILED   {name}: {e:#}");
+            }
+        }
+    }
+
+    for (job, reason) in &missing {
+        println!(
+            "missing  day {} part {} ({}): {reason}",
+            job.day, job.part, job.input
+        );
+    }
+
+    println!(
+        "\n{} checks, {failures} failed, {} known missing",
+        checks.len(),
+        missing.len()
+    );
+
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::answers::{diff, Answers, Recorded};
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            Answers::parse("part 1:\n13140\npart 2:\n##..\n#..#\n").unwrap(),
+            Answers {
+                part1: Some(Recorded::Answer("13140".to_string())),
+                part2: Some(Recorded::Answer("##..\n#..#".to_string()))
+            }
+        );
+        assert_eq!(
+            Answers::parse("part 2:\n5\n").unwrap(),
+            Answers {
+                part1: None,
+                part2: Some(Recorded::Answer("5".to_string()))
+            }
+        );
+        assert_eq!(
+            Answers::parse("part 1:\n5\npart 2: known missing, too slow\n").unwrap(),
+            Answers {
+                part1: Some(Recorded::Answer("5".to_string())),
+                part2: Some(Recorded::KnownMissing("too slow".to_string()))
+            }
+        );
+        assert!(Answers::parse("5\n").is_err());
+        assert!(Answers::parse("part 1:\n5\npart 1:\n6").is_err());
+        assert!(Answers::parse("part 1: 5\n").is_err());
+        assert!(Answers::parse("part 1: known missing, slow\n6\n").is_err());
+    }
+
+    #[test]
+    fn diff_test() {
+        assert_eq!(diff("a\nb\nc", "a\nx"), "  a\n- b\n+ x\n- c");
+    }
+}
+
+// one test per recorded answer for every input_FLAVOUR.txt in data, see build.rs
+#[cfg(test)]
+mod data_tests {
+    use adventofcode2022::DayPart;
+    use pretty_assertions::assert_eq;
+
+    use crate::answers::{load, Recorded};
+    use crate::runner::{self, Input, Job};
+
+    fn check(year: u16, day: u8, part: DayPart, flavour: &str) {
+        let answers = load(year, day, Some(flavour))
+            .unwrap()
+            .expect("build.rs only generates tests for recorded answers");
+        let Some(Recorded::Answer(expected)) = answers.get(part) else {
+            panic!("build.rs only generates tests for recorded answers");
+        };
+        let job = Job {
+            year,
+            day,
+            part,
+            input: Input::Data(Some(flavour.to_string())),
+        };
+
+        let result = runner::run(adventofcode2022::year(year).unwrap(), job, None);
+
+        assert_eq!(result.outcome.unwrap().to_string(), *expected);
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/data_tests.rs"));
+}
@@ -0,0 +1,230 @@
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use adventofcode2022::{take_parsed_at, Answer, Year};
+use color_eyre::eyre::{eyre, WrapErr};
+use itertools::Itertools;
+
+use crate::runner::Job;
+
+#[derive(Debug, Clone)]
+pub(crate) struct BenchOpts {
+    pub(crate) iterations: usize,
+    pub(crate) warmup: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Stats {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(samples: &[Duration]) -> Self {
+        let sorted = samples.iter().copied().sorted().collect::<Vec<_>>();
+        let n = sorted.len();
+
+        let median = if n % 2 == 0 {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        let mean = sorted.iter().sum::<Duration>().as_secs_f64() / n as f64;
+        // sample standard deviation, a single run doesn't have any spread
+        let variance = if n > 1 {
+            sorted
+                .iter()
+                .map(|d| (d.as_secs_f64() - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64
+        } else {
+            0.0
+        };
+
+        Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+
+    fn to_json(self) -> String {
+        format!(
+            "{{\"min_ns\": {}, \"median_ns\": {}, \"mean_ns\": {}, \"stddev_ns\": {}}}",
+            self.min.as_nanos(),
+            self.median.as_nanos(),
+            self.mean.as_nanos(),
+            self.stddev.as_nanos()
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct BenchResult {
+    job: Job,
+    opts: BenchOpts,
+    answer: Answer,
+    total: Stats,
+    // only known for solvers that call daylib::mark_parsed
+    parse: Option<Stats>,
+    solve: Option<Stats>,
+}
+
+pub(crate) fn bench(year: &Year, job: Job, opts: &BenchOpts) -> color_eyre::Result<BenchResult> {
+    let solver = year.day(job.day)?.solver(job.part);
+    let input = job.read_input()?;
+
+    for _ in 0..opts.warmup {
+        solver(&input).wrap_err("solver error")?;
+    }
+
+    let mut totals = Vec::with_capacity(opts.iterations);
+    let mut parses = Vec::with_capacity(opts.iterations);
+    let mut solves = Vec::with_capacity(opts.iterations);
+    let mut answer = None;
+
+    for _ in 0..opts.iterations {
+        take_parsed_at();
+
+        let start = Instant::now();
+        answer = Some(solver(&input).wrap_err("solver error")?);
+        let end = Instant::now();
+
+        totals.push(end - start);
+        if let Some(parsed_at) = take_parsed_at() {
+            parses.push(parsed_at - start);
+            solves.push(end - parsed_at);
+        }
+    }
+
+    let split = parses.len() == totals.len();
+
+    Ok(BenchResult {
+        job,
+        opts: opts.clone(),
+        answer: answer.ok_or_else(|| eyre!("no iterations to measure"))?,
+        total: Stats::new(&totals),
+        parse: split.then(|| Stats::new(&parses)),
+        solve: split.then(|| Stats::new(&solves)),
+    })
+}
+
+pub(crate) fn print(result: &BenchResult) {
+    let BenchResult {
+        job,
+        opts,
+        answer,
+        total,
+        parse,
+        solve,
+    } = result;
+
+    println!(
+        "day {} part {} ({}): {} runs after {} warm-up, answer {}",
+        job.day,
+        job.part,
+        job.input,
+        opts.iterations,
+        opts.warmup,
+        answer.to_string().lines().next().unwrap_or_default()
+    );
+    println!(
+        "       {:>12}  {:>12}  {:>12}  {:>12}",
+        "min", "median", "mean", "stddev"
+    );
+    for (name, stats) in [("parse", parse), ("solve", solve), ("total", &Some(*total))] {
+        if let Some(s) = stats {
+            println!(
+                "{name:<5}  {:>12}  {:>12}  {:>12}  {:>12}",
+                format!("{:.2?}", s.min),
+                format!("{:.2?}", s.median),
+                format!("{:.2?}", s.mean),
+                format!("{:.2?}", s.stddev)
+            );
+        }
+    }
+    println!();
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// integers stay numbers and grids become an array of rows
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Int(n) => n.to_string(),
+        Answer::Text(s) => json_string(s),
+        Answer::Grid(rows) => format!("[{}]", rows.iter().map(|r| json_string(r)).join(", ")),
+    }
+}
+
+pub(crate) fn to_json(results: &[BenchResult]) -> String {
+    let mut entries = results.iter().map(|r| {
+        format!(
+            "  {{\"year\": {}, \"day\": {}, \"part\": {}, \"input\": {}, \"iterations\": {}, \
+             \"warmup\": {}, \"answer\": {}, \"parse\": {}, \"solve\": {}, \"total\": {}}}",
+            r.job.year,
+            r.job.day,
+            r.job.part,
+            json_string(&r.job.input.to_string()),
+            r.opts.iterations,
+            r.opts.warmup,
+            json_answer(&r.answer),
+            r.parse.map_or("null".to_string(), Stats::to_json),
+            r.solve.map_or("null".to_string(), Stats::to_json),
+            r.total.to_json()
+        )
+    });
+
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use adventofcode2022::Answer;
+
+    use crate::bench::{json_answer, json_string, Stats};
+
+    #[test]
+    fn stats_test() {
+        let stats = Stats::new(&[4, 1, 3, 2].map(Duration::from_millis));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        // sqrt(5/3) ms
+        assert_eq!(stats.stddev.as_micros(), 1290);
+    }
+
+    #[test]
+    fn json_string_test() {
+        assert_eq!(json_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+
+    #[test]
+    fn json_answer_test() {
+        assert_eq!(json_answer(&Answer::Int(-3)), "-3");
+        assert_eq!(json_answer(&"2=-1=0".into()), "\"2=-1=0\"");
+        assert_eq!(
+            json_answer(&Answer::Grid(vec!["#.".to_string(), ".#".to_string()])),
+            "[\"#.\", \".#\"]"
+        );
+    }
+}
@@ -1,56 +1,56 @@
 use std::collections::BinaryHeap;
 use std::iter;
 
-use crate::daylib::Day;
+use color_eyre::eyre::eyre;
+
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, ParseError};
 
 #[derive(Debug)]
 struct Elf {
     calories: Vec<u32>,
 }
 
-fn parse(input: &str) -> Vec<Elf> {
+fn parse(input: &str) -> Result<Vec<Elf>, ParseError> {
     input
         .split("\n\n")
         .map(str::trim)
         .map(|x| {
             x.split('\n')
-                .map(str::parse)
-                .map(Result::unwrap)
-                .collect::<Vec<_>>()
+                .map(|calories| parse_at(input, calories))
+                .collect::<Result<Vec<_>, _>>()
         })
-        .map(|calories| Elf { calories })
-        .collect::<Vec<_>>()
+        .map(|calories| Ok(Elf { calories: calories? }))
+        .collect()
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let elves = parse(input);
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let elves = parse(input)?;
+    mark_parsed();
 
-    Ok(elves
+    elves
         .iter()
         .map(|elf| elf.calories.iter().copied().sum::<u32>())
         .max()
-        .unwrap()
-        .to_string())
+        .map(Answer::from)
+        .ok_or_else(|| eyre!("no elves"))
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let elves = parse(input);
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let elves = parse(input)?;
+    mark_parsed();
 
     let mut sorted = elves
         .iter()
         .map(|elf| elf.calories.iter().copied().sum::<u32>())
         .collect::<BinaryHeap<u32>>();
 
-    Ok(iter::from_fn(|| sorted.pop())
-        .take(3)
-        .sum::<u32>()
-        .to_string())
+    Ok(iter::from_fn(|| sorted.pop()).take(3).sum::<u32>().into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 1,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
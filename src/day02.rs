@@ -1,9 +1,9 @@
-use std::fmt;
 use std::str::FromStr;
 
 use itertools::Itertools;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_lines, ParseError};
 
 #[derive(Debug)]
 enum Shape {
@@ -12,20 +12,21 @@ enum Shape {
     Scissors,
 }
 
-fn parse_shape(s: &str) -> Shape {
-    match s {
-        "A" | "X" => Shape::Rock,
-        "B" | "Y" => Shape::Paper,
-        "C" | "Z" => Shape::Scissors,
-        _ => panic!("unknown shape {s}"),
+// `s` is the line `shape` is in
+fn parse_shape(s: &str, shape: &str) -> Result<Shape, ParseError> {
+    match shape {
+        "A" | "X" => Ok(Shape::Rock),
+        "B" | "Y" => Ok(Shape::Paper),
+        "C" | "Z" => Ok(Shape::Scissors),
+        _ => Err(ParseError::new(s, shape, format!("unknown shape {shape:?}"))),
     }
 }
 
-fn parse_line(s: &str) -> (Shape, Shape) {
+fn parse_line(s: &str) -> Result<(Shape, Shape), ParseError> {
     if let Some((theirs, mine)) = s.split_whitespace().collect_tuple() {
-        (parse_shape(theirs), parse_shape(mine))
+        Ok((parse_shape(s, theirs)?, parse_shape(s, mine)?))
     } else {
-        panic!("can't parse line {s}")
+        Err(ParseError::new(s, s, "expected two shapes"))
     }
 }
 
@@ -50,14 +51,15 @@ fn score((theirs, mine): (Shape, Shape)) -> u32 {
     shape_score + win_score
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    Ok(input
-        .split('\n')
-        .map(parse_line)
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let rounds = parse_lines(input, parse_line)?;
+    mark_parsed();
+
+    Ok(rounds
+        .into_iter()
         .map(score)
         .sum::<u32>()
-        .to_string())
+        .into())
 }
 
 enum GameResult {
@@ -66,17 +68,8 @@ enum GameResult {
     Win,
 }
 
-#[derive(Debug, Clone)]
-struct GameResultParseError(String);
-
-impl fmt::Display for GameResultParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "unknown game result {}", self.0)
-    }
-}
-
 impl FromStr for GameResult {
-    type Err = GameResultParseError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use GameResult::{Draw, Lose, Win};
@@ -85,16 +78,23 @@ impl FromStr for GameResult {
             "X" => Ok(Lose),
             "Y" => Ok(Draw),
             "Z" => Ok(Win),
-            other => Err(GameResultParseError(other.to_string())),
+            other => Err(ParseError::new(
+                s,
+                s,
+                format!("unknown game result {other:?}"),
+            )),
         }
     }
 }
 
-fn parse_line_game_result(s: &str) -> (Shape, GameResult) {
+fn parse_line_game_result(s: &str) -> Result<(Shape, GameResult), ParseError> {
     if let Some((theirs, result)) = s.split_whitespace().collect_tuple() {
-        (parse_shape(theirs), GameResult::from_str(result).unwrap())
+        Ok((
+            parse_shape(s, theirs)?,
+            GameResult::from_str(result).map_err(|e| e.within(s, result))?,
+        ))
     } else {
-        panic!("can't parse line {s}")
+        Err(ParseError::new(s, s, "expected a shape and a game result"))
     }
 }
 
@@ -118,19 +118,21 @@ fn infer_mine((theirs, result): (Shape, GameResult)) -> (Shape, Shape) {
     (theirs, mine)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    Ok(input
-        .split('\n')
-        .map(parse_line_game_result)
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let rounds = parse_lines(input, parse_line_game_result)?;
+    mark_parsed();
+
+    Ok(rounds
+        .into_iter()
         .map(infer_mine)
         .map(score)
         .sum::<u32>()
-        .to_string())
+        .into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 2,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
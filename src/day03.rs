@@ -1,9 +1,11 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 
+use color_eyre::eyre::eyre;
 use itertools::Itertools;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_lines, ParseError};
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone, Hash)]
 struct ItemType {
@@ -37,9 +39,20 @@ impl Rucksack {
 }
 
 impl FromStr for Rucksack {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((i, c)) = s.char_indices().find(|(_, c)| !c.is_ascii_alphabetic()) {
+            return Err(ParseError::new(s, &s[i..], format!("invalid item type {c:?}")));
+        }
+        if s.len() % 2 != 0 {
+            return Err(ParseError::new(
+                s,
+                s,
+                format!("can't split {} items into two compartments", s.len()),
+            ));
+        }
+
         let (first, second) = s.split_at(s.len() / 2);
         Ok(Rucksack(
             first.chars().map(|code| ItemType { code }).collect(),
@@ -48,12 +61,12 @@ impl FromStr for Rucksack {
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    Ok(input
-        .split('\n')
-        .map(Rucksack::from_str)
-        .map(Result::unwrap)
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let rucksacks = parse_lines(input, Rucksack::from_str)?;
+    mark_parsed();
+
+    Ok(rucksacks
+        .into_iter()
         .map(|r| {
             r.0.intersection(&r.1)
                 .copied()
@@ -62,15 +75,15 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
                 .sum::<u16>()
         })
         .sum::<u16>()
-        .to_string())
+        .into())
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    Ok(input
-        .split('\n')
-        .map(Rucksack::from_str)
-        .map(Result::unwrap)
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let rucksacks = parse_lines(input, Rucksack::from_str)?;
+    mark_parsed();
+
+    Ok(rucksacks
+        .into_iter()
         .chunks(3)
         .into_iter()
         .map(|mut chunk| {
@@ -83,21 +96,17 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
                     .collect()
             })
         })
-        .inspect(|badge_items| {
-            assert_eq!(
-                badge_items.len(),
-                1,
-                "only one badge per elf group, got {:?}",
-                badge_items
-            );
+        .map(|badge_items| match badge_items.iter().exactly_one() {
+            Ok(badge) => Ok(u16::from(badge.priority())),
+            Err(_) => Err(eyre!("only one badge per elf group, got {badge_items:?}")),
         })
-        .map(|badge_items| u16::from(badge_items.iter().next().unwrap().priority()))
-        .sum::<u16>()
-        .to_string())
+        .sum::<color_eyre::Result<u16>>()?
+        .into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 3,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,41 +1,34 @@
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-use color_eyre::eyre::{eyre, WrapErr};
-
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, parse_lines, ParseError};
 
 type Range = RangeInclusive<usize>;
 
 #[derive(Debug)]
 struct ElfPair(Range, Range);
 
-fn parse_range(s: &str) -> color_eyre::Result<Range> {
+fn parse_range(s: &str) -> Result<Range, ParseError> {
     let mut iter = s.split('-');
     let (Some(from_s), Some(to_s), None) = (iter.next(), iter.next(), iter.next()) else {
-        return Err(eyre!("malformed SectionRange: {s:?}"));
+        return Err(ParseError::new(s, s, "malformed SectionRange"));
     };
-    let from = from_s
-        .parse()
-        .wrap_err_with(|| format!("can't parse {from_s:?} into int"))?;
-    let to = to_s
-        .parse()
-        .wrap_err_with(|| format!("can't parse {to_s:?} into int"))?;
-    Ok(from..=to)
+    Ok(parse_at(s, from_s)?..=parse_at(s, to_s)?)
 }
 
 impl FromStr for ElfPair {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut iter = s.split(',');
         let (Some(a_s), Some(b_s), None) = (iter.next(), iter.next(), iter.next()) else {
-            return Err(eyre!("malformed ElfPair: {s:?}"))
+            return Err(ParseError::new(s, s, "malformed ElfPair"));
         };
 
         Ok(ElfPair(
-            parse_range(a_s).wrap_err_with(|| format!("can't parse {a_s:?} into a range"))?,
-            parse_range(b_s).wrap_err_with(|| format!("can't parse {b_s:?} into a range"))?,
+            parse_range(a_s).map_err(|e| e.within(s, a_s))?,
+            parse_range(b_s).map_err(|e| e.within(s, b_s))?,
         ))
     }
 }
@@ -67,15 +60,17 @@ fn test_is_included_in() {
     assert!(!range(0, 4).contains_range(&range(1, 5)));
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let elf_pairs = parse_lines(input, ElfPair::from_str)?;
+    mark_parsed();
+
     let mut n: usize = 0;
-    for pair in input.lines().map(ElfPair::from_str) {
-        let ElfPair(a, b) = pair?;
+    for ElfPair(a, b) in elf_pairs {
         if a.contains_range(&b) || b.contains_range(&a) {
             n += 1;
         }
     }
-    Ok(n.to_string())
+    Ok(n.try_into()?)
 }
 
 fn is_overlapping(a: &Range, b: &Range) -> bool {
@@ -98,20 +93,19 @@ fn test_is_overlapping() {
     assert!(!is_overlapping(&range(0, 1), &range(2, 4)));
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let elf_pairs = input
-        .lines()
-        .map(ElfPair::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let elf_pairs = parse_lines(input, ElfPair::from_str)?;
+    mark_parsed();
     Ok(elf_pairs
         .iter()
         .filter(|pair| is_overlapping(&pair.0, &pair.1))
         .count()
-        .to_string())
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 4,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
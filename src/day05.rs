@@ -3,7 +3,8 @@ use std::str::FromStr;
 use color_eyre::eyre::eyre;
 use itertools::Itertools;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, parse_lines, ParseError};
 
 #[derive(Debug)]
 struct Crate(char);
@@ -28,22 +29,29 @@ struct Move {
 }
 
 impl FromStr for Move {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let iter = s.split(' ');
         let Some(("move", n_s, "from", from_s, "to", to_s)) = iter.collect_tuple() else {
-            return Err(eyre!("malformed Move: {s:?}"))
+            return Err(ParseError::new(s, s, "malformed Move"));
         };
 
         Ok(Move {
-            from: from_s.parse::<usize>()? - 1,
-            to: to_s.parse::<usize>()? - 1,
-            n: n_s.parse::<usize>()?,
+            from: parse_stack(s, from_s)?,
+            to: parse_stack(s, to_s)?,
+            n: parse_at(s, n_s)?,
         })
     }
 }
 
+// stacks are numbered from 1 in the input and indexed from 0 here
+fn parse_stack(s: &str, at: &str) -> Result<usize, ParseError> {
+    parse_at::<usize>(s, at)?
+        .checked_sub(1)
+        .ok_or_else(|| ParseError::new(s, at, "stacks are numbered from 1"))
+}
+
 // bottom-up
 #[derive(Debug)]
 struct Stacks(Vec<Vec<Crate>>);
@@ -81,11 +89,11 @@ impl Stacks {
 }
 
 impl FromStr for Stacks {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut stacks = Stacks(vec![]);
-        for _ in 0..((s.lines().next().unwrap().len() + 1) / 4) {
+        for _ in 0..((s.lines().next().map_or(0, str::len) + 1) / 4) {
             stacks.0.push(Vec::new());
         }
 
@@ -99,7 +107,14 @@ impl FromStr for Stacks {
                     (Some(' '), Some(' '), Some(' ')) => continue,
                     // this is the last line of the diagram
                     (Some(' '), Some('1'), Some(' ')) => break 'outer,
-                    other => return Err(eyre!("malformed line {line:?}, encountered {other:?}")),
+                    other => {
+                        let at = line.get(i * 4..).unwrap_or(line);
+                        return Err(ParseError::new(
+                            s,
+                            at,
+                            format!("expected a crate like [A], got {other:?}"),
+                        ));
+                    }
                 }
             }
         }
@@ -110,42 +125,41 @@ impl FromStr for Stacks {
     }
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
     let Some((stack_part, moves_part)) = input.split("\n\n").collect_tuple() else {
-        return Err(eyre!("can't split the file into stack and moves parts"))
+        return Err(eyre!("can't split the file into stack and moves parts"));
     };
 
-    let moves = moves_part
-        .lines()
-        .map(Move::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
+    let moves =
+        parse_lines(moves_part, Move::from_str).map_err(|e| e.within(input, moves_part))?;
 
-    let mut stacks = Stacks::from_str(stack_part)?;
+    let mut stacks = Stacks::from_str(stack_part).map_err(|e| e.within(input, stack_part))?;
+    mark_parsed();
 
     stacks.apply_9000(moves);
 
-    Ok(stacks.bottom_crates())
+    Ok(stacks.bottom_crates().into())
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
     let Some((stack_part, moves_part)) = input.split("\n\n").collect_tuple() else {
-        return Err(eyre!("can't split the file into stack and moves parts"))
+        return Err(eyre!("can't split the file into stack and moves parts"));
     };
 
-    let moves = moves_part
-        .lines()
-        .map(Move::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
+    let moves =
+        parse_lines(moves_part, Move::from_str).map_err(|e| e.within(input, moves_part))?;
 
-    let mut stacks = Stacks::from_str(stack_part)?;
+    let mut stacks = Stacks::from_str(stack_part).map_err(|e| e.within(input, stack_part))?;
+    mark_parsed();
 
     stacks.apply_9001(moves);
 
-    Ok(stacks.bottom_crates())
+    Ok(stacks.bottom_crates().into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 5,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
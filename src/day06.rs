@@ -2,7 +2,8 @@ use std::str;
 
 use color_eyre::eyre::eyre;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::ParseError;
 
 fn to_idx(c: u8) -> usize {
     (c - b'a') as usize
@@ -12,6 +13,19 @@ fn to_idx(c: u8) -> usize {
 //     println!("{}: {}", i, str::from_utf8(window).unwrap());
 // }
 
+// the signal without trailing whitespace, `to_idx` only works for lowercase letters
+fn parse(input: &str) -> Result<&str, ParseError> {
+    let signal = input.trim_end();
+    match signal.char_indices().find(|(_, c)| !c.is_ascii_lowercase()) {
+        Some((i, c)) => Err(ParseError::new(
+            input,
+            &signal[i..],
+            format!("unexpected {c:?} in the signal"),
+        )),
+        None => Ok(signal),
+    }
+}
+
 fn find_marker(s: &str, len: usize) -> Option<usize> {
     let mut counts = [false; 26];
     let mut last: Option<u8> = None;
@@ -30,16 +44,22 @@ fn find_marker(s: &str, len: usize) -> Option<usize> {
     None
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    match find_marker(input, 4) {
-        Some(n) => Ok(n.to_string()),
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let signal = parse(input)?;
+    mark_parsed();
+
+    match find_marker(signal, 4) {
+        Some(n) => Ok(n.try_into()?),
         None => Err(eyre!("didn't find unique window of length 4 in the input")),
     }
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    match find_marker(input, 14) {
-        Some(n) => Ok(n.to_string()),
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let signal = parse(input)?;
+    mark_parsed();
+
+    match find_marker(signal, 14) {
+        Some(n) => Ok(n.try_into()?),
         None => Err(eyre!("didn't find unique window of length 4 in the input")),
     }
 }
@@ -48,4 +68,5 @@ pub(crate) const DAY: Day = Day {
     number: 6,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,22 +1,24 @@
 use std::collections::HashMap;
 
+use color_eyre::eyre::eyre;
 use itertools::Itertools;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
 
 pub(crate) mod parser {
-    use color_eyre::eyre::eyre;
     use nom::sequence::preceded;
     use nom::{
         branch::alt,
         bytes::complete::tag,
-        character::complete::{alphanumeric1, digit1},
+        character::complete::alphanumeric1,
         combinator::{map, opt, recognize},
         multi::{many0, separated_list0},
         sequence::{delimited, separated_pair, tuple},
-        Finish, IResult,
+        IResult,
     };
 
+    use crate::shared::{parse_all, parse_unum, ParseError};
+
     #[derive(Debug, Eq, PartialEq)]
     pub(crate) enum CdKind {
         UpOne,
@@ -53,15 +55,15 @@ pub(crate) mod parser {
     fn parse_ls(input: &str) -> IResult<&str, Command> {
         let file_line = map(
             separated_pair(
-                digit1,
+                parse_unum,
                 tag(" "),
                 recognize(tuple((
                     alphanumeric1,
                     opt(tuple((tag("."), alphanumeric1))),
                 ))),
             ),
-            |(size_s, name): (&str, &str)| DirContent::File {
-                size: size_s.parse().unwrap(),
+            |(size, name): (usize, &str)| DirContent::File {
+                size,
                 name: name.to_string(),
             },
         );
@@ -82,17 +84,8 @@ pub(crate) mod parser {
         )(input)
     }
 
-    pub(crate) fn parse(input: &str) -> color_eyre::Result<Vec<Command>> {
-        many0(alt((parse_cd, parse_ls)))(input)
-            .finish()
-            .map(|(tail, result)| {
-                assert!(
-                    tail.is_empty(),
-                    "unexpected non-empty tail: \n{tail}\n\nresult: {result:?}"
-                );
-                result
-            })
-            .map_err(|e| eyre!("parse error {e}"))
+    pub(crate) fn parse(input: &str) -> Result<Vec<Command>, ParseError> {
+        parse_all(input, many0(alt((parse_cd, parse_ls))))
     }
 
     #[cfg(test)]
@@ -219,7 +212,7 @@ impl Dir {
     }
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
     fn walker(d: &Dir) -> usize {
         let raw_self_size = d.size();
         let self_size = if raw_self_size <= 100_000 {
@@ -238,13 +231,15 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
 
     let commands = parser::parse(input)?;
     let root = Dir::from_commands(commands);
+    mark_parsed();
 
-    Ok(walker(&root).to_string())
+    Ok(walker(&root).try_into()?)
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
     let commands = parser::parse(input)?;
     let root = Dir::from_commands(commands);
+    mark_parsed();
 
     let mut dirs: Vec<&Dir> = Vec::new();
     {
@@ -258,18 +253,22 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
         walker(&mut dirs, &root);
     }
 
-    let space_needed = 30_000_000 - (70_000_000 - root.size());
+    let free = 70_000_000_usize
+        .checked_sub(root.size())
+        .ok_or_else(|| eyre!("{} of files don't fit on the disk", root.size()))?;
+    let space_needed = 30_000_000_usize.saturating_sub(free);
     Ok(dirs
         .iter()
         .map(|d| d.size())
         .sorted()
         .find(|x| *x >= space_needed)
-        .unwrap()
-        .to_string())
+        .ok_or_else(|| eyre!("no directory frees up {space_needed}"))?
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 7,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
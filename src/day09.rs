@@ -2,10 +2,11 @@ use std::collections::HashSet;
 use std::iter;
 use std::str::FromStr;
 
-use color_eyre::eyre::eyre;
 use itertools::Itertools;
+use log::trace;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, parse_lines, ParseError};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 struct Position {
@@ -90,7 +91,7 @@ enum Direction {
 }
 
 impl FromStr for Direction {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use Direction::{Down, Left, Right, Up};
@@ -99,7 +100,11 @@ impl FromStr for Direction {
             "D" => Ok(Down),
             "L" => Ok(Left),
             "R" => Ok(Right),
-            other => Err(eyre!("unknown direction {other:?}")),
+            other => Err(ParseError::new(
+                s,
+                s,
+                format!("unknown direction {other:?}"),
+            )),
         }
     }
 }
@@ -111,17 +116,17 @@ struct Command {
 }
 
 impl FromStr for Command {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut iter = s.split(' ');
         if let (Some(d_s), Some(n_s), None) = (iter.next(), iter.next(), iter.next()) {
             Ok(Command {
-                d: d_s.parse()?,
-                n: n_s.parse()?,
+                d: Direction::from_str(d_s).map_err(|e| e.within(s, d_s))?,
+                n: parse_at(s, n_s)?,
             })
         } else {
-            Err(eyre!("malformed command {s:?}"))
+            Err(ParseError::new(s, s, "malformed command"))
         }
     }
 }
@@ -158,7 +163,7 @@ fn draw_states<const N: usize>(states: &Vec<State<N>>) {
             screen[state.rope[piece].y as usize][state.rope[piece].x as usize] = glyph;
         }
 
-        println!(
+        trace!(
             "{}\n\n",
             Itertools::intersperse(
                 screen
@@ -197,14 +202,9 @@ fn model<const N: usize>(commands: &[Command]) -> Vec<State<N>> {
     states
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let commands = input
-        .lines()
-        //.take(1)
-        .map(Command::from_str)
-        .map(Result::unwrap)
-        .collect_vec();
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let commands = parse_lines(input, Command::from_str)?;
+    mark_parsed();
     let states = model::<2>(&commands);
 
     //draw_states(&states);
@@ -214,17 +214,12 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
         .map(|s| *s.rope.last().unwrap())
         .collect::<HashSet<_>>()
         .len()
-        .to_string())
+        .try_into()?)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let commands = input
-        .lines()
-        //.take(1)
-        .map(Command::from_str)
-        .map(Result::unwrap)
-        .collect_vec();
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let commands = parse_lines(input, Command::from_str)?;
+    mark_parsed();
     let states = model::<10>(&commands);
 
     //draw_states(&states);
@@ -234,11 +229,12 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
         .map(|s| *s.rope.last().unwrap())
         .collect::<HashSet<_>>()
         .len()
-        .to_string())
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 9,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,9 +1,10 @@
 use std::str::FromStr;
 
-use color_eyre::eyre::eyre;
 use itertools::Itertools;
+use log::debug;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, parse_lines, ParseError};
 
 #[derive(Debug)]
 enum Line {
@@ -12,7 +13,7 @@ enum Line {
 }
 
 impl FromStr for Line {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use Line::{AddX, Noop};
@@ -20,8 +21,8 @@ impl FromStr for Line {
         let mut iter = s.split(' ');
         match (iter.next(), iter.next(), iter.next()) {
             (Some("noop"), None, None) => Ok(Noop),
-            (Some("addx"), Some(x), None) => Ok(AddX(x.parse().unwrap())),
-            other => Err(eyre!("unknown command {other:?}")),
+            (Some("addx"), Some(x), None) => Ok(AddX(parse_at(s, x)?)),
+            _ => Err(ParseError::new(s, s, "unknown command")),
         }
     }
 }
@@ -44,14 +45,12 @@ fn simulate_lines(lines: &[Line]) -> Vec<isize> {
         .collect()
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let register_values = simulate_lines(
-        &input
-            .lines()
-            .map(Line::from_str)
-            .collect::<Result<Vec<_>, _>>()?,
-    );
-    println!(
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let lines = parse_lines(input, Line::from_str)?;
+    mark_parsed();
+
+    let register_values = simulate_lines(&lines);
+    debug!(
         "{} {} {} {} {} {}",
         register_values[20 - 1],
         register_values[60 - 1],
@@ -67,43 +66,39 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
         + 140 * register_values[140 - 1]
         + 180 * register_values[180 - 1]
         + 220 * register_values[220 - 1])
-        .to_string())
+        .try_into()?)
 }
 
 const SCREEN_WIDTH: usize = 40;
 
 #[allow(clippy::cast_possible_wrap)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let register_values = simulate_lines(
-        &input
-            .lines()
-            .map(Line::from_str)
-            .collect::<Result<Vec<_>, _>>()?,
-    );
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let lines = parse_lines(input, Line::from_str)?;
+    mark_parsed();
+
+    let register_values = simulate_lines(&lines);
+
+    let screen = register_values
+        .iter()
+        .enumerate()
+        .map(|(crt, register)| {
+            if ((crt % SCREEN_WIDTH) as isize - *register).abs() <= 1 {
+                '#'
+            } else {
+                '.'
+            }
+        })
+        .chunks(SCREEN_WIDTH)
+        .into_iter()
+        .map(Iterator::collect::<String>)
+        .collect();
 
-    let screen = Itertools::intersperse(
-        register_values
-            .iter()
-            .enumerate()
-            .map(|(crt, register)| {
-                if ((crt % SCREEN_WIDTH) as isize - *register).abs() <= 1 {
-                    '#'
-                } else {
-                    '.'
-                }
-            })
-            .chunks(SCREEN_WIDTH)
-            .into_iter()
-            .map(Iterator::collect::<String>),
-        "\n".to_string(),
-    )
-    .collect::<String>();
-
-    Ok(screen)
+    Ok(Answer::Grid(screen))
 }
 
 pub(crate) const DAY: Day = Day {
     number: 10,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,4 +1,5 @@
 use itertools::Itertools;
+use log::{debug, trace};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -9,7 +10,8 @@ use nom::{
     IResult,
 };
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::parse_all;
 
 #[derive(Debug, Clone, Copy)]
 enum Term {
@@ -149,17 +151,13 @@ impl Monkey {
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve(
-    input: &str,
-    rounds: u32,
-    reduce_worry: bool,
-    print_trace: bool,
-) -> color_eyre::Result<String> {
+fn solve(input: &str, rounds: u32, reduce_worry: bool) -> color_eyre::Result<Answer> {
     let mut state = input
         .split("\n\n")
-        .map(|s| Monkey::parse(s).unwrap().1)
-        .collect::<Vec<_>>();
+        .map(|s| parse_all(s, Monkey::parse).map_err(|e| e.within(input, s)))
+        .collect::<Result<Vec<_>, _>>()?;
+    mark_parsed();
+
     let mut counts = vec![0u64; state.len()];
 
     let divider_mult = state.iter().map(|m| m.test_divider).product::<u32>();
@@ -178,39 +176,35 @@ fn solve(
             }
             state[monkey_i].items.drain(..);
         }
-        if print_trace {
-            println!(
-                "after round {round_i}:\n{}\n\n",
-                Itertools::intersperse(
-                    state.iter().map(|m| format!(
-                        "Monkey {}: {:?}",
-                        m.id,
-                        m.items.iter().map(|x| x.worry).collect::<Vec<_>>()
-                    )),
-                    "\n".to_string()
-                )
-                .collect::<String>()
-            );
-        }
+        trace!(
+            "after round {round_i}:\n{}\n\n",
+            Itertools::intersperse(
+                state.iter().map(|m| format!(
+                    "Monkey {}: {:?}",
+                    m.id,
+                    m.items.iter().map(|x| x.worry).collect::<Vec<_>>()
+                )),
+                "\n".to_string()
+            )
+            .collect::<String>()
+        );
     }
-    println!("inspections: {counts:?}");
+    debug!("inspections: {counts:?}");
 
-    Ok(format!(
-        "monkey business level: {}",
-        counts.iter().sorted().rev().take(2).product::<u64>()
-    ))
+    Ok(counts.iter().sorted().rev().take(2).product::<u64>().try_into()?)
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    solve(input, 20, true, false)
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    solve(input, 20, true)
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    solve(input, 10_000, false, false)
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    solve(input, 10_000, false)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 11,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
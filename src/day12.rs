@@ -1,7 +1,12 @@
-use binary_heap_plus as bhp;
+use std::cmp::Ordering;
+
+use color_eyre::eyre::eyre;
 use itertools::Itertools;
+use log::trace;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day, Render, Rendered};
+use crate::shared::search::{a_star, bfs_distances, Path, Search};
+use crate::shared::{Grid, Image, ParseError, Rgb};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 enum MapPoint {
@@ -20,271 +25,265 @@ impl MapPoint {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-struct RowCol(usize, usize);
-
-impl RowCol {
-    fn row(&self) -> usize {
-        self.0
-    }
-
-    fn col(&self) -> usize {
-        self.1
-    }
-}
+// x, y
+type Pos = (usize, usize);
 
 // 0;0 is top left
 #[derive(Debug, Clone)]
 struct Map {
-    data: Vec<MapPoint>,
-    start: RowCol,
-    end: RowCol,
-    size: RowCol,
+    data: Grid<MapPoint>,
+    start: Pos,
+    end: Pos,
 }
 
 impl Map {
-    fn parse(input: &str) -> Self {
+    fn parse(input: &str) -> Result<Self, ParseError> {
         use MapPoint::{End, Point, Start};
 
-        let cols = input.lines().next().unwrap().chars().count();
-        let rows = input.lines().count();
-        let mut start = None;
-        let mut end = None;
-        Self {
-            data: input
-                .chars()
-                .filter(|c| *c != '\n' && *c != '\r')
-                .enumerate()
-                .map(|(i, c)| match c {
-                    'S' => {
-                        start = Some(RowCol(i / cols, i % cols));
-                        Start
-                    }
-                    'E' => {
-                        end = Some(RowCol(i / cols, i % cols));
-                        End
-                    }
-                    other => Point(other as u8),
-                })
-                .collect(),
-            start: start.unwrap(),
-            end: end.unwrap(),
-            size: RowCol(rows, cols),
-        }
-    }
+        let data = Grid::parse(input, |c| match c {
+            'S' => Some(Start),
+            'E' => Some(End),
+            'a'..='z' => Some(Point(c as u8)),
+            _ => None,
+        })?;
+        let find = |point| data.cells().find(|(_, p)| **p == point).map(|(pos, _)| pos);
 
-    #[allow(dead_code)]
-    fn string_map(&self) -> String {
-        Itertools::intersperse(
-            self.data.chunks(self.size.col()).map(|row| {
-                row.iter()
-                    .map(|p| match p {
-                        MapPoint::Start => 'S',
-                        MapPoint::End => 'E',
-                        MapPoint::Point(p) => *p as char,
-                    })
-                    .collect::<String>()
-            }),
-            "\n".to_string(),
-        )
-        .collect::<String>()
+        let (Some(start), Some(end)) = (find(Start), find(End)) else {
+            return Err(ParseError::new(input, input, "expected a start (S) and an end (E)"));
+        };
+        Ok(Self { data, start, end })
     }
 
-    fn idx(&self, x: RowCol) -> usize {
-        x.0 * self.size.col() + x.1
+    fn chars(&self) -> Grid<char> {
+        self.data.map(|p| match p {
+            MapPoint::Start => 'S',
+            MapPoint::End => 'E',
+            MapPoint::Point(p) => *p as char,
+        })
     }
 
-    fn elevation(&self, x: RowCol) -> u8 {
-        self.data[self.idx(x)].elevation()
+    fn elevation(&self, x: Pos) -> u8 {
+        self.data[x].elevation()
     }
 
     #[allow(clippy::cast_lossless)]
-    fn neighbours(&self, x: RowCol) -> Vec<RowCol> {
-        let up = if x.row() > 0 {
-            Some(RowCol(x.0 - 1, x.1))
-        } else {
-            None
-        };
-        let down = if x.row() < (self.size.row() - 1) {
-            Some(RowCol(x.0 + 1, x.1))
-        } else {
-            None
-        };
-        let left = if x.col() > 0 {
-            Some(RowCol(x.0, x.1 - 1))
-        } else {
-            None
-        };
-        let right = if x.col() < (self.size.col() - 1) {
-            Some(RowCol(x.0, x.1 + 1))
-        } else {
-            None
-        };
+    fn neighbours(&self, x: Pos) -> impl Iterator<Item = Pos> + '_ {
         let x_elevation = self.elevation(x);
 
-        vec![up, down, left, right]
-            .iter()
-            .flatten()
-            .copied()
+        self.data
+            .neighbours4(x)
             // can get negative if we're higher than the heighbour and that's OK
-            .filter(|p| (self.elevation(*p) as i16) - (x_elevation as i16) <= 1)
-            .collect()
+            .filter(move |p| (self.elevation(*p) as i16) - (x_elevation as i16) <= 1)
+    }
+
+    // the fewest steps from every cell to the end, None where the end can't be reached from
+    fn distance_field(&self) -> Grid<Option<u32>> {
+        let mut field = self.data.map(|_| None);
+        for (pos, distance) in bfs_distances(&Descent(self), self.end) {
+            field[pos] = Some(distance);
+        }
+        field
     }
 }
 
-#[derive(Debug)]
-struct SearchNode {
-    pos: RowCol,
-    path_len: u32,
-    full_path_guess: u32,
+// walking back down from the end, so the climbing rules are reversed
+struct Descent<'a>(&'a Map);
+
+impl Search for Descent<'_> {
+    type State = Pos;
+
+    #[allow(clippy::cast_lossless)]
+    fn successors(&self, pos: &Pos) -> Vec<(Pos, u32)> {
+        let map = self.0;
+        let elevation = map.elevation(*pos);
+
+        map.data
+            .neighbours4(*pos)
+            .filter(|p| (elevation as i16) - (map.elevation(*p) as i16) <= 1)
+            .map(|p| (p, 1))
+            .collect()
+    }
+
+    fn is_goal(&self, _pos: &Pos) -> bool {
+        false
+    }
 }
 
-fn h(map: &Map, pos: RowCol) -> u32 {
-    u32::try_from(map.end.0.abs_diff(pos.0) + map.end.1.abs_diff(pos.1)).unwrap()
+// distances scaled to 0..=9 and rounded, 0 is at the end and 9 is the furthest square
+fn render_distance_field(field: &Grid<Option<u32>>) -> String {
+    let longest = field.iter().flatten().max().copied().unwrap_or(0).max(1);
+    field.render(|d| match d {
+        Some(d) => char::from_digit((d * 9 + longest / 2) / longest, 10).unwrap(),
+        None => '.',
+    })
 }
 
-fn a_star(map: &Map) -> (Vec<Option<u32>>, Vec<Option<RowCol>>) {
-    // shortest known path from the start
-    let mut min_path: Vec<Option<u32>> = vec![None; map.data.len()];
-    min_path[map.idx(map.start)] = Some(0);
+impl Search for Map {
+    type State = Pos;
 
-    // where did we get to it from
-    let mut min_path_from: Vec<Option<RowCol>> = vec![None; map.data.len()];
-    min_path_from[map.idx(map.start)] = None;
+    fn successors(&self, pos: &Pos) -> Vec<(Pos, u32)> {
+        self.neighbours(*pos).map(|p| (p, 1)).collect()
+    }
 
-    let mut frontier = bhp::BinaryHeap::new_by(|a: &SearchNode, b: &SearchNode| {
-        a.full_path_guess.cmp(&b.full_path_guess).reverse()
-    });
+    fn is_goal(&self, pos: &Pos) -> bool {
+        *pos == self.end
+    }
 
-    let start_node = SearchNode {
-        pos: map.start,
-        path_len: 0,
-        full_path_guess: h(map, map.start),
-    };
-    frontier.push(start_node);
+    fn heuristic(&self, pos: &Pos) -> u32 {
+        u32::try_from(self.end.0.abs_diff(pos.0) + self.end.1.abs_diff(pos.1)).unwrap()
+    }
+}
 
-    while !frontier.is_empty() {
-        let node = frontier.pop().unwrap();
+// the map with every step of the path drawn as an arrow in the direction it goes
+fn path_map(map: &Map, path: &Path<Pos>) -> Grid<char> {
+    let mut path_map = map.chars();
+    for (from, to) in path.states.iter().tuple_windows() {
+        path_map[*from] = match (to.0.cmp(&from.0), to.1.cmp(&from.1)) {
+            (Ordering::Greater, _) => '>',
+            (Ordering::Less, _) => '<',
+            (_, Ordering::Greater) => 'v',
+            (_, Ordering::Less) => '^',
+            _ => panic!("path stays at {from:?}"),
+        };
+    }
+    path_map
+}
 
-        if node.pos == map.end {
-            break;
-        }
+fn shortest_path(map: &Map) -> color_eyre::Result<Path<Pos>> {
+    a_star(map, map.start).ok_or_else(|| eyre!("no path from S to E"))
+}
 
-        for nei_pos in map.neighbours(node.pos) {
-            let nei_idx = map.idx(nei_pos);
-            let new_path = node.path_len + 1;
-            let old_path = min_path[nei_idx];
-            if old_path.is_none() || new_path < old_path.unwrap() {
-                min_path[nei_idx] = Some(new_path);
-                min_path_from[nei_idx] = Some(node.pos);
-                frontier.push(SearchNode {
-                    pos: nei_pos,
-                    path_len: new_path,
-                    full_path_guess: new_path + h(map, nei_pos),
-                });
-            }
-        }
-    }
+// pixels per map cell in the heat map
+const HEAT_MAP_SCALE: usize = 4;
 
-    // println!(
-    //     "{}",
-    //     Itertools::intersperse(
-    //         visited.chunks(map.size.col()).map(|line| line
-    //             .iter()
-    //             .map(|x| format!("{:?}\t", x))
-    //             .collect::<String>()),
-    //         "\n".to_string()
-    //     )
-    //     .collect::<String>()
-    // );
-
-    (min_path, min_path_from)
+// blue next to the start through green to red furthest away, black where S can't get to
+fn heat_colour(distance: Option<u32>, longest: u32) -> Rgb {
+    let Some(distance) = distance else {
+        return [0, 0, 0];
+    };
+    let t = f64::from(distance) / f64::from(longest.max(1));
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let channel = |x: f64| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [
+        channel(2.0 * t - 1.0),
+        channel(1.0 - (2.0 * t - 1.0).abs()),
+        channel(1.0 - 2.0 * t),
+    ]
 }
 
-#[allow(dead_code)]
-fn print_move_map(map: &Map, min_path_from: &[Option<RowCol>]) {
-    let mut move_map = vec!['.'; map.data.len()];
-    move_map[map.idx(map.end)] = 'E';
-    let mut pos = map.end;
-    while pos != map.start {
-        let from_pos = min_path_from[map.idx(pos)]
-            .unwrap_or_else(|| panic!("expected to find path to {pos:?}"));
-        // there's a bug in idea rust plugin that makes "other" seem unused
-        #[allow(unused_variables)]
-        match (
-            0isize
-                .checked_add_unsigned(pos.row())
-                .unwrap()
-                .checked_sub_unsigned(from_pos.row())
-                .unwrap(),
-            0isize
-                .checked_add_unsigned(pos.col())
-                .unwrap()
-                .checked_sub_unsigned(from_pos.col())
-                .unwrap(),
-        ) {
-            (0, 1) => move_map[map.idx(from_pos)] = '>',
-            (0, -1) => move_map[map.idx(from_pos)] = '<',
-            (1, 0) => move_map[map.idx(from_pos)] = 'v',
-            (-1, 0) => move_map[map.idx(from_pos)] = '^',
-            other => panic!("unexpected move {other:?}"),
-        }
-        pos = from_pos;
+fn render_path(input: &str) -> color_eyre::Result<Rendered> {
+    let map = Map::parse(input)?;
+    Ok(Rendered::Text(path_map(&map, &shortest_path(&map)?).to_string()))
+}
+
+fn render_distances(input: &str) -> color_eyre::Result<Rendered> {
+    let map = Map::parse(input)?;
+    Ok(Rendered::Text(render_distance_field(&map.distance_field())))
+}
+
+fn render_heat_map(input: &str) -> color_eyre::Result<Rendered> {
+    let map = Map::parse(input)?;
+
+    let mut distances = map.data.map(|_| None);
+    for (pos, distance) in bfs_distances(&map, map.start) {
+        distances[pos] = Some(distance);
     }
-    println!(
-        "{}",
-        Itertools::intersperse(
-            move_map
-                .chunks(map.size.col())
-                .map(|line| line.iter().collect::<String>()),
-            "\n".to_string()
-        )
-        .collect::<String>()
+    let longest = distances.iter().flatten().max().copied().unwrap_or(0);
+
+    let mut image = Image::new(
+        map.data.width() * HEAT_MAP_SCALE,
+        map.data.height() * HEAT_MAP_SCALE,
+        [0, 0, 0],
     );
+    for ((x, y), distance) in distances.cells() {
+        image.fill_square(
+            (x * HEAT_MAP_SCALE, y * HEAT_MAP_SCALE),
+            HEAT_MAP_SCALE,
+            heat_colour(*distance, longest),
+        );
+    }
+    Ok(Rendered::Image(image))
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let map = Map::parse(input);
-    let (min_path, _min_path_from) = a_star(&map);
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let map = Map::parse(input)?;
+    mark_parsed();
+    let path = shortest_path(&map)?;
+    trace!("shortest path:\n{}", path_map(&map, &path));
 
-    Ok(min_path[map.idx(map.end)].unwrap().to_string())
+    Ok(path.cost.into())
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let map = Map::parse(input);
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let map = Map::parse(input)?;
+    mark_parsed();
+
+    let field = map.distance_field();
+    trace!("distances to the end:\n{}", render_distance_field(&field));
 
     Ok(map
         .data
-        .iter()
-        .enumerate()
-        .filter_map(|(i, mp)| {
-            if mp.elevation() == b'a' {
-                Some(i)
-            } else {
-                None
-            }
-        })
-        .filter_map(|new_start_idx| {
-            let mut map = map.clone();
-            let old_start_idx = map.idx(map.start);
-            map.data[old_start_idx] = MapPoint::Point(b'a');
-            map.data[new_start_idx] = MapPoint::Start;
-            map.start = RowCol(
-                new_start_idx / map.size.col(),
-                new_start_idx % map.size.col(),
-            );
-            let (min_path, _min_path_from) = a_star(&map);
-            min_path[map.idx(map.end)]
-        })
+        .cells()
+        .filter(|(_, mp)| mp.elevation() == b'a')
+        .filter_map(|(pos, _)| field[pos])
         .min()
-        .unwrap()
-        .to_string())
+        .ok_or_else(|| eyre!("no path from any a to E"))?
+        .into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 12,
     part1: solve1,
     part2: solve2,
+    renders: &[
+        Render {
+            name: "path",
+            help: "the map with the shortest path from S to E drawn as arrows",
+            render: render_path,
+        },
+        Render {
+            name: "distances",
+            help: "how far every square is from E, scaled to 0-9 with 9 for the furthest square",
+            render: render_distances,
+        },
+        Render {
+            name: "heatmap",
+            help: "an image of how many steps every square is from S",
+            render: render_heat_map,
+        },
+    ],
 };
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::daylib::Rendered;
+    use crate::day12::{path_map, shortest_path, Map, DAY};
+
+    const INPUT: &str = "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi";
+
+    #[test]
+    fn distance_field_test() {
+        let map = Map::parse(INPUT).unwrap();
+        let field = map.distance_field();
+
+        assert_eq!(field[map.start], Some(31));
+        assert_eq!(field[map.end], Some(0));
+        assert_eq!(field[(0, 4)], Some(29));
+        assert_eq!(
+            (DAY.render("distances").unwrap().render)(INPUT).unwrap(),
+            Rendered::Text("99834445\n98831115\n98830015\n98832226\n88777666".to_string())
+        );
+    }
+
+    #[test]
+    fn path_map_test() {
+        let map = Map::parse(INPUT).unwrap();
+        let path = shortest_path(&map).unwrap();
+
+        assert_eq!(path.cost, 31);
+        assert_eq!(path_map(&map, &path).iter().filter(|c| "<>^v".contains(**c)).count(), 31);
+        assert_eq!(path_map(&map, &path).row(2)[5], 'E');
+    }
+}
@@ -1,6 +1,7 @@
 use std::cmp;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::parse_all;
 use itertools::Itertools;
 use nom::{
     branch::alt,
@@ -9,7 +10,7 @@ use nom::{
     combinator::{map, map_res},
     multi::separated_list0,
     sequence::{delimited, separated_pair},
-    Finish, IResult,
+    IResult,
 };
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -73,29 +74,36 @@ fn parse_pair(i: &str) -> IResult<&str, (Item, Item)> {
     separated_pair(Item::parse, tag("\n"), Item::parse)(i)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    Ok(input
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let pairs = input
         .split("\n\n")
-        .map(|s| parse_pair(s).finish().unwrap().1)
-        .map(|(l, r)| l.cmp(&r))
+        .map(|s| parse_all(s, parse_pair).map_err(|e| e.within(input, s)))
+        .collect::<Result<Vec<_>, _>>()?;
+    mark_parsed();
+
+    Ok(pairs
+        .iter()
+        .map(|(l, r)| l.cmp(r))
         .enumerate()
         .filter_map(|(i, ord)| if ord.is_lt() { Some(i + 1) } else { None })
         .sum::<usize>()
-        .to_string())
+        .try_into()?)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
     let divider_packets = vec![
         Item::parse("[[2]]").unwrap().1,
         Item::parse("[[6]]").unwrap().1,
     ];
-
-    Ok(input
+    let packets = input
         .split('\n')
         .filter(|s| !s.is_empty())
-        .map(|s| Item::parse(s).unwrap().1)
+        .map(|s| parse_all(s, Item::parse).map_err(|e| e.within(input, s)))
+        .collect::<Result<Vec<_>, _>>()?;
+    mark_parsed();
+
+    Ok(packets
+        .into_iter()
         .chain(divider_packets.clone())
         .sorted()
         .enumerate()
@@ -107,11 +115,12 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
             }
         })
         .product::<usize>()
-        .to_string())
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 13,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,9 +1,11 @@
 use std::ops::{Index, IndexMut};
-use std::{cmp, iter, usize};
+use std::{cmp, iter};
 
 use itertools::Itertools;
+use log::trace;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, ParseError, SparseGrid};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Material {
@@ -14,66 +16,46 @@ enum Material {
     Floor,
 }
 
-const MAX_WIDTH: usize = 1000;
-
 #[derive(Debug)]
-struct Grid {
-    data: Vec<Material>,
-    w: usize,
-    h: usize,
-    min_seen_x: usize,
-    max_seen_x: usize,
+struct Cave {
+    data: SparseGrid<(isize, isize), Material>,
+    // the floor in part 2, the abyss in part 1
+    floor_y: isize,
     has_floor: bool,
-    sand_source: (usize, usize),
+    sand_source: (isize, isize),
 }
 
-impl Grid {
+impl Cave {
     fn new(
-        sand_source: (usize, usize),
-        traces: &Vec<Vec<(usize, usize)>>,
+        sand_source: (isize, isize),
+        traces: &Vec<Vec<(isize, isize)>>,
         has_floor: bool,
     ) -> Self {
-        let points = traces
+        let max_seen_y = traces
             .iter()
             .flatten()
             .chain(iter::once(&sand_source))
-            .collect::<Vec<_>>();
-        let min_seen_x = *points.iter().map(|(x, _)| x).min().unwrap();
-        let max_seen_x = *points.iter().map(|(x, _)| x).max().unwrap();
-        let max_seen_y = *points.iter().map(|(_, y)| y).max().unwrap();
-
-        let w = MAX_WIDTH;
-        // +1 to account for size vs value, +2 to add floor/abyss
-        let h = max_seen_y + 3;
-        let mut grid = Grid {
-            data: vec![Material::Air; w * h],
-            w,
-            h,
-            min_seen_x,
-            max_seen_x,
+            .map(|(_, y)| *y)
+            .max()
+            .unwrap();
+
+        let mut grid = Cave {
+            data: SparseGrid::new(Material::Air),
+            floor_y: max_seen_y + 2,
             has_floor,
             sand_source,
         };
 
-        for x in 0..MAX_WIDTH {
-            // set .data directly to skip max/min_seen checks
-            grid.data[grid.w * (grid.h - 1) + x] = if has_floor {
-                Material::Floor
-            } else {
-                Material::Abyss
-            };
-        }
-
         for trace in traces {
             for ((xa, ya), (xb, yb)) in trace.iter().tuple_windows() {
                 if xa == xb {
                     for y in cmp::min(*ya, *yb)..=cmp::max(*ya, *yb) {
-                        grid[(*xa, y)] = Material::Rock;
+                        grid.data[(*xa, y)] = Material::Rock;
                     }
                 } else {
-                    assert_eq!(ya, yb);
+                    // parse_traces made sure it's horizontal then
                     for x in cmp::min(*xa, *xb)..=cmp::max(*xa, *xb) {
-                        grid[(x, *ya)] = Material::Rock;
+                        grid.data[(x, *ya)] = Material::Rock;
                     }
                 }
             }
@@ -83,14 +65,16 @@ impl Grid {
     }
 
     fn format(&self) -> String {
-        Itertools::intersperse(
-            self.data.chunks(self.w).map(|line| {
-                use Material::{Abyss, Air, Floor, Rock, Sand};
-
-                line.iter()
-                    .skip(self.min_seen_x - 1)
-                    .take(self.max_seen_x - self.min_seen_x + 2)
-                    .map(|m| match m {
+        use Material::{Abyss, Air, Floor, Rock, Sand};
+
+        let ((min_x, _), (max_x, _)) = self
+            .data
+            .bounds()
+            .unwrap_or((self.sand_source, self.sand_source));
+        (0..=self.floor_y)
+            .map(|y| {
+                (min_x - 1..=max_x + 1)
+                    .map(|x| match self[(x, y)] {
                         Rock => '#',
                         Air => '.',
                         Sand => 'o',
@@ -98,59 +82,46 @@ impl Grid {
                         Floor => 'F',
                     })
                     .collect::<String>()
-            }),
-            "\n".to_string(),
-        )
-        .collect::<String>()
+            })
+            .join("\n")
     }
 }
 
-impl Index<(usize, usize)> for Grid {
+impl Index<(isize, isize)> for Cave {
     type Output = Material;
 
-    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        self.data
-            .get(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+    fn index(&self, pos @ (_, y): (isize, isize)) -> &Self::Output {
+        match (y == self.floor_y, self.has_floor) {
+            (true, true) => &Material::Floor,
+            (true, false) => &Material::Abyss,
+            (false, _) => &self.data[pos],
+        }
     }
 }
 
-impl IndexMut<(usize, usize)> for Grid {
-    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        if x < self.min_seen_x {
-            self.min_seen_x = x;
-        } else if x > self.max_seen_x {
-            self.max_seen_x = x;
-        }
-
-        self.data
-            .get_mut(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+impl IndexMut<(isize, isize)> for Cave {
+    fn index_mut(&mut self, pos: (isize, isize)) -> &mut Self::Output {
+        &mut self.data[pos]
     }
 }
 
 #[derive(Debug, Copy, Clone)]
 enum StepResult {
-    Rest(usize, usize),
-    Move(usize, usize),
+    Rest(isize, isize),
+    Move(isize, isize),
     Abyss,
 }
 
-fn step(grid: &Grid, _sand @ (x, y): (usize, usize)) -> StepResult {
+fn step(grid: &Cave, _sand @ (x, y): (isize, isize)) -> StepResult {
     use StepResult::{Abyss, Move, Rest};
 
-    if !grid.has_floor && y == grid.h - 2 {
+    if !grid.has_floor && y == grid.floor_y - 1 {
         return Abyss;
     }
 
-    if let Some((new_x, new_y)) = vec![(0, 1), (-1, 1), (1, 1)]
+    if let Some((new_x, new_y)) = [(0, 1), (-1, 1), (1, 1)]
         .iter()
-        .map(|(dx, dy)| {
-            (
-                x.checked_add_signed(*dx).unwrap(),
-                y.checked_add_signed(*dy).unwrap(),
-            )
-        })
+        .map(|(dx, dy)| (x + dx, y + dy))
         .find(|new_pos| grid[*new_pos] == Material::Air)
     {
         Move(new_x, new_y)
@@ -159,26 +130,38 @@ fn step(grid: &Grid, _sand @ (x, y): (usize, usize)) -> StepResult {
     }
 }
 
-const SAND_SOURCE: (usize, usize) = (500, 0);
+const SAND_SOURCE: (isize, isize) = (500, 0);
 
-fn parse_traces(s: &str) -> color_eyre::Result<Vec<Vec<(usize, usize)>>> {
+// every trace goes straight from one point to the next
+fn parse_traces(s: &str) -> Result<Vec<Vec<(isize, isize)>>, ParseError> {
     s.lines()
         .map(|line| {
-            line.split(" -> ")
+            let points = line
+                .split(" -> ")
                 .map(|pair_s| {
                     let mut it = pair_s.split(',');
                     if let (Some(a_s), Some(b_s), None) = (it.next(), it.next(), it.next()) {
-                        Ok::<_, color_eyre::Report>((a_s.parse::<usize>()?, b_s.parse::<usize>()?))
+                        Ok(((parse_at(s, a_s)?, parse_at(s, b_s)?), pair_s))
                     } else {
-                        panic!("unexpected pair format {pair_s:?}")
+                        Err(ParseError::new(s, pair_s, "expected a point like 498,4"))
                     }
                 })
-                .collect::<Result<Vec<_>, _>>()
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some((_, (_, to_s))) = points
+                .iter()
+                .tuple_windows()
+                .find(|(((xa, ya), _), ((xb, yb), _))| xa != xb && ya != yb)
+            {
+                return Err(ParseError::new(s, to_s, "traces can't go diagonally"));
+            }
+
+            Ok(points.into_iter().map(|(point, _)| point).collect())
         })
-        .collect::<Result<Vec<_>, _>>()
+        .collect()
 }
 
-fn simulate(mut grid: Grid) -> Grid {
+fn simulate(mut grid: Cave) -> Cave {
     loop {
         use StepResult::{Abyss, Move, Rest};
 
@@ -192,10 +175,10 @@ fn simulate(mut grid: Grid) -> Grid {
             Abyss => break,
             // part 2, stop when there the source is blocked
             Rest(x, y) if (x, y) == grid.sand_source => {
-                grid[(x, y)] = Material::Sand;
+                grid.data[(x, y)] = Material::Sand;
                 break;
             }
-            Rest(x, y) => grid[(x, y)] = Material::Sand,
+            Rest(x, y) => grid.data[(x, y)] = Material::Sand,
             Move(_, _) => unreachable!(),
         }
     }
@@ -203,42 +186,45 @@ fn simulate(mut grid: Grid) -> Grid {
     grid
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
     let traces = parse_traces(input)?;
+    mark_parsed();
 
-    let mut grid = Grid::new(SAND_SOURCE, &traces, false);
-    println!("pre-simulation grid:\n{}\n", grid.format());
+    let mut grid = Cave::new(SAND_SOURCE, &traces, false);
+    trace!("pre-simulation grid:\n{}\n", grid.format());
 
     grid = simulate(grid);
-    println!("post-simulation grid:\n{}\n", grid.format());
+    trace!("post-simulation grid:\n{}\n", grid.format());
 
     Ok(grid
         .data
-        .iter()
+        .values()
         .filter(|m| **m == Material::Sand)
         .count()
-        .to_string())
+        .try_into()?)
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
     let traces = parse_traces(input)?;
+    mark_parsed();
 
-    let mut grid = Grid::new(SAND_SOURCE, &traces, true);
-    println!("pre-simulation grid:\n{}\n", grid.format());
+    let mut grid = Cave::new(SAND_SOURCE, &traces, true);
+    trace!("pre-simulation grid:\n{}\n", grid.format());
 
     grid = simulate(grid);
-    println!("post-simulation grid:\n{}\n", grid.format());
+    trace!("post-simulation grid:\n{}\n", grid.format());
 
     Ok(grid
         .data
-        .iter()
+        .values()
         .filter(|m| **m == Material::Sand)
         .count()
-        .to_string())
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 14,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use itertools::Itertools;
+use log::debug;
 use nom::{
     bytes::complete::tag,
     combinator::map,
@@ -11,8 +12,8 @@ use rand::distributions::{Distribution, Uniform};
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
 
-use crate::daylib::Day;
-use crate::shared::parse_inum;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_all, parse_at, parse_inum, ParseError};
 
 #[derive(Debug)]
 struct BeaconReport {
@@ -44,14 +45,34 @@ impl BeaconReport {
     }
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
+// (the row to check in part 1, the search space size in part 2, reports),
+// the example and the real input differ in both so they're on the first two lines
+fn parse(input: &str) -> Result<(i64, i64, Vec<BeaconReport>), ParseError> {
     let mut lines = input.lines();
-    let test_y: i64 = lines.next().unwrap().parse()?;
-    // ignore explicit search space line
-    let _ = lines.next();
+    let (Some(test_y_s), Some(search_space_s)) = (lines.next(), lines.next()) else {
+        return Err(ParseError::new(
+            input,
+            input,
+            "expected the test row and the search space size first",
+        ));
+    };
+    let reports = lines
+        .map(|l| parse_all(l, BeaconReport::parse).map_err(|e| e.within(input, l)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((
+        parse_at(input, test_y_s)?,
+        parse_at(input, search_space_s)?,
+        reports,
+    ))
+}
+
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let (test_y, _search_space, reports) = parse(input)?;
+    mark_parsed();
 
-    let relevant_reports = lines
-        .map(|l| BeaconReport::parse(l).unwrap().1)
+    let relevant_reports = reports
+        .into_iter()
         .filter(|br| br.sensor.1.abs_diff(test_y) <= br.r)
         .collect::<Vec<_>>();
 
@@ -77,7 +98,7 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
         .filter(|x| relevant_reports.iter().any(|br| br.in_range((*x, test_y))))
         .count();
 
-    Ok((xs_in_range - num_beacons_on_test_y).to_string())
+    Ok((xs_in_range - num_beacons_on_test_y).try_into()?)
 }
 
 fn repulsion((x, y): (i64, i64), reports: &[BeaconReport]) -> i64 {
@@ -102,7 +123,7 @@ fn better_neighbour(
 ) -> Option<(i64, i64)> {
     let repulsion_now = repulsion(now, reports);
 
-    vec![(0, 1), (0, -1), (1, 0), (-1, 0)]
+    [(0, 1), (0, -1), (1, 0), (-1, 0)]
         .iter()
         .map(|(dx, dy)| (x + dx, y + dy))
         .filter(|(x, y)| *x >= 0 && *x <= search_space && *y >= 0 && *y <= search_space)
@@ -115,15 +136,9 @@ fn better_neighbour(
 
 // doesn't work, local minima defeat it :(
 #[allow(dead_code)]
-fn solve2_localsearch(input: &str) -> color_eyre::Result<String> {
-    let mut lines = input.lines();
-    // ignore the test line
-    let _ = lines.next();
-    let search_space: i64 = lines.next().unwrap().parse()?;
-
-    let reports = lines
-        .map(|l| BeaconReport::parse(l).unwrap().1)
-        .collect::<Vec<_>>();
+fn solve2_localsearch(input: &str) -> color_eyre::Result<Answer> {
+    let (_test_y, search_space, reports) = parse(input)?;
+    mark_parsed();
 
     let known_beacons = reports.iter().map(|br| br.beacon).collect::<HashSet<_>>();
 
@@ -139,45 +154,36 @@ fn solve2_localsearch(input: &str) -> color_eyre::Result<String> {
         }
         let final_repulsion = repulsion(test_point, &reports);
         if final_repulsion > 0 {
-            println!("[{loops}] found a local minimum at {test_point:?} ({final_repulsion})");
+            debug!("[{loops}] found a local minimum at {test_point:?} ({final_repulsion})");
             loops += 1;
             continue;
         }
         if known_beacons.contains(&test_point) {
-            println!("[{loops}] found a known beacon at {test_point:?}");
+            debug!("[{loops}] found a known beacon at {test_point:?}");
             loops += 1;
             continue;
         }
 
-        println!("[{loops}] found the hidden beacon at {test_point:?}!");
+        debug!("[{loops}] found the hidden beacon at {test_point:?}!");
         hidden_beacon = test_point;
         break;
     }
 
-    Ok((hidden_beacon.0 * 4_000_000 + hidden_beacon.1).to_string())
+    Ok((hidden_beacon.0 * 4_000_000 + hidden_beacon.1).into())
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let mut lines = input.lines();
-    // ignore the test line
-    let _ = lines.next();
-    let search_space: i64 = lines.next().unwrap().parse()?;
-
-    let reports = lines
-        .map(|l| BeaconReport::parse(l).unwrap().1)
-        .collect::<Vec<_>>();
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let (_test_y, search_space, reports) = parse(input)?;
+    mark_parsed();
 
     let mut result = None;
 
     let sides = vec![(0, -1), (0, 1), (-1, 0), (1, 0)];
-    for (
-        _i,
-        BeaconReport {
-            beacon: _,
-            sensor,
-            r,
-        },
-    ) in reports.iter().enumerate()
+    for BeaconReport {
+        beacon: _,
+        sensor,
+        r,
+    } in &reports
     {
         for offset in 0..=(r + 1) {
             for (a, b) in &sides {
@@ -211,11 +217,12 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
 
     let result = result.unwrap();
 
-    Ok((result.0 * 4_000_000 + result.1).to_string())
+    Ok((result.0 * 4_000_000 + result.1).into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 15,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,21 +1,23 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::time::Instant;
-use std::{cmp, fmt, vec};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::str::FromStr;
+use std::{cmp, fmt, iter};
 
+use color_eyre::eyre::{eyre, WrapErr};
 use itertools::Itertools;
+use log::{debug, log_enabled, Level};
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::alpha1,
+    bytes::complete::{tag, take_while_m_n},
     combinator::map,
     multi::separated_list1,
     sequence::{preceded, tuple},
     IResult,
 };
-use rayon::prelude::*;
 
-use crate::daylib::Day;
-use crate::shared::parse_unum;
+use crate::daylib::{mark_parsed, Answer, Day, DayPart, Render, Rendered};
+use crate::shared::search::{dfs_bound, BranchAndBound};
+use crate::shared::{parse_all, parse_lines, parse_unum, Grid};
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
 struct ValveName([char; 2]);
@@ -29,11 +31,16 @@ impl ValveName {
             panic!("can't produce ValveName from {s}");
         }
     }
+
+    // without the brackets
+    fn code(self) -> String {
+        self.0.iter().collect()
+    }
 }
 
 impl fmt::Display for ValveName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{}]", self.0.iter().collect::<String>())
+        write!(f, "[{}]", self.code())
     }
 }
 
@@ -44,284 +51,900 @@ struct Valve {
     tunnels: Vec<ValveName>,
 }
 
+fn parse_valve_name(i: &str) -> IResult<&str, ValveName> {
+    map(
+        take_while_m_n(2, 2, |c: char| c.is_ascii_uppercase()),
+        ValveName::new,
+    )(i)
+}
+
 impl Valve {
     fn parse(i: &str) -> IResult<&str, Self> {
         map(
             tuple((
-                preceded(tag("Valve "), alpha1),
+                preceded(tag("Valve "), parse_valve_name),
                 preceded(tag(" has flow rate="), parse_unum::<u32>),
                 preceded(
                     alt((
                         tag("; tunnels lead to valves "),
                         tag("; tunnel leads to valve "),
                     )),
-                    separated_list1(tag(", "), alpha1),
+                    separated_list1(tag(", "), parse_valve_name),
                 ),
             )),
             |(name, flow, tunnels)| Valve {
-                name: ValveName::new(name),
+                name,
                 flow,
-                tunnels: tunnels.into_iter().map(ValveName::new).collect(),
+                tunnels,
             },
         )(i)
     }
 }
 
-fn shortest_path(
-    valve_index: &HashMap<ValveName, Valve>,
-    from: ValveName,
-    to: ValveName,
-    seen: &mut HashSet<ValveName>,
-) -> Option<u32> {
-    let mut q = valve_index[&from]
-        .tunnels
-        .iter()
-        .copied()
-        .map(|name| (1, name))
-        .collect::<VecDeque<_>>();
+// the tunnels compiled down to integer indices and the distances between every two valves.
+// the valves with a flow come first, highest flow first, so valve i is bit i of a valve set
+struct Network {
+    names: Vec<ValveName>,
+    flows: Vec<u32>,
+    tunnels: Vec<Vec<usize>>,
+    // how many valves have a flow
+    with_flow: usize,
+    // fewest minutes from one valve (x) to another (y)
+    distances: Grid<u32>,
+    start: usize,
+}
+
+impl Network {
+    fn new(all_valves: &[Valve], start: ValveName) -> color_eyre::Result<Self> {
+        let valves = all_valves
+            .iter()
+            .sorted_by_key(|v| (v.flow == 0, cmp::Reverse(v.flow)))
+            .collect::<Vec<_>>();
+        let index = valves
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name, i))
+            .collect::<HashMap<_, _>>();
+        let start = *index
+            .get(&start)
+            .ok_or_else(|| eyre!("there's no valve {start} to start from"))?;
+
+        let with_flow = valves.iter().filter(|v| v.flow > 0).count();
+        if with_flow > 64 {
+            return Err(eyre!(
+                "{with_flow} valves with a flow, valve sets only fit 64"
+            ));
+        }
 
-    while let Some((distance, name)) = q.pop_front() {
-        if name == to {
-            return Some(distance);
+        let tunnels = valves
+            .iter()
+            .map(|v| {
+                v.tunnels
+                    .iter()
+                    .map(|to| {
+                        index
+                            .get(to)
+                            .copied()
+                            .ok_or_else(|| eyre!("{} has a tunnel to a missing {to}", v.name))
+                    })
+                    .collect::<color_eyre::Result<Vec<_>>>()
+            })
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+
+        // Floyd-Warshall
+        let n = valves.len();
+        let mut distances = Grid::new(n, n, u32::MAX);
+        for (from, to) in tunnels.iter().enumerate() {
+            distances[(from, from)] = 0;
+            for to in to {
+                distances[(from, *to)] = 1;
+            }
         }
-        if seen.contains(&name) {
-            continue;
+        for via in 0..n {
+            for from in 0..n {
+                for to in 0..n {
+                    let d = distances[(from, via)].saturating_add(distances[(via, to)]);
+                    if d < distances[(from, to)] {
+                        distances[(from, to)] = d;
+                    }
+                }
+            }
         }
 
-        seen.insert(name);
-        for next_name in &valve_index[&name].tunnels {
-            q.push_back((distance + 1, *next_name));
+        if let Some(v) = (0..with_flow).find(|v| distances[(start, *v)] == u32::MAX) {
+            return Err(eyre!(
+                "there's no way from {} to {}",
+                valves[start].name,
+                valves[v].name
+            ));
         }
+
+        Ok(Network {
+            names: valves.iter().map(|v| v.name).collect(),
+            flows: valves.iter().map(|v| v.flow).collect(),
+            tunnels,
+            with_flow,
+            distances,
+            start,
+        })
     }
 
-    None
-}
+    // all the valves with a flow
+    fn all(&self) -> u64 {
+        u64::MAX
+            .checked_shr(u32::try_from(64 - self.with_flow).unwrap())
+            .unwrap_or(0)
+    }
 
-fn search(
-    valve_index: &HashMap<ValveName, Valve>,
-    nonzero_valves: &[ValveName],
-    shortest_paths: &HashMap<(ValveName, ValveName), u32>,
-    start: ValveName,
-    initial_time: u32,
-) -> (u32, Vec<ValveName>) {
-    #[derive(Debug, Clone)]
-    struct Node {
-        valve: ValveName,
-        time_left: u32,
-        release: u32,
-        path: Vec<ValveName>,
-        valves_left: Vec<ValveName>,
-    }
-
-    let mut frontier = vec![Node {
-        valve: start,
-        time_left: initial_time,
-        release: 0,
-        path: vec![],
-        valves_left: nonzero_valves
-            .iter()
-            .filter(|n| **n != start)
-            // we use presorting here to avoid re-sorting in the trimming heuristic below
-            .sorted_by_key(|n| cmp::Reverse(valve_index[n].flow))
-            .copied()
-            .collect(),
-    }];
-    frontier.reserve(100);
-    let mut best = frontier.last().unwrap().clone();
-
-    //let mut bails = 0;
-    //let mut nodes = 0;
+    // a valve set's valves, highest flow first
+    fn valves(mut set: u64) -> impl Iterator<Item = usize> {
+        iter::from_fn(move || {
+            let v = set.trailing_zeros();
+            set &= set.wrapping_sub(1);
+            (v < 64).then_some(v as usize)
+        })
+    }
 
-    while let Some(node) = frontier.pop() {
-        // println!(
-        //     "{}standing at{} (will release {}, {} time left), arrived by {}",
-        //     " ".repeat(node.time_left as usize),
-        //     node.valve,
-        //     node.release,
-        //     node.time_left,
-        //     Itertools::intersperse(node.path.iter().map(|x| format!("{x}")), "-".to_string())
-        //         .collect::<String>()
-        // );
-
-        //nodes += 1;
-
-        if node.release > best.release {
-            best = node.clone();
+    // the tunnels a shortest path from one valve to another goes through, without `from`
+    fn path(&self, from: usize, to: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut at = from;
+        while at != to {
+            at = *self.tunnels[at]
+                .iter()
+                .find(|next| self.distances[(**next, to)] + 1 == self.distances[(at, to)])
+                .expect("a shortest path goes through one of the tunnels");
+            path.push(at);
         }
+        path
+    }
+}
 
-        let upper_bound = node.release
-            + node
-                .valves_left
-                .iter()
-                .enumerate()
-                .map(|(i, n)| {
+#[derive(Debug, Clone)]
+struct Node {
+    valve: usize,
+    time_left: u32,
+    release: u32,
+    valves_left: u64,
+    opened: u64,
+}
+
+// opening valves one after another, walking the shortest paths between them
+struct Opening<'a> {
+    network: &'a Network,
+}
+
+impl BranchAndBound for Opening<'_> {
+    type State = Node;
+    type Score = u32;
+
+    fn successors(&self, node: &Node, out: &mut Vec<Node>) {
+        out.extend(Network::valves(node.valves_left).filter_map(|v| {
+            let shortest_path = self.network.distances[(node.valve, v)];
+            let new_time_left = node.time_left.checked_sub(shortest_path + 1)?;
+
+            Some(Node {
+                valve: v,
+                time_left: new_time_left,
+                release: node.release + self.network.flows[v] * new_time_left,
+                valves_left: node.valves_left & !(1 << v),
+                opened: node.opened | 1 << v,
+            })
+        }));
+    }
+
+    fn score(&self, node: &Node) -> u32 {
+        node.release
+    }
+
+    fn bound(&self, node: &Node) -> u32 {
+        node.release
+            + Network::valves(node.valves_left)
+                .zip(1..)
+                .map(|(v, i)| {
                     // next valve is at least 2 time units away
-                    // valves_left is sorted in descending flow order, which means we're
+                    // valves come in descending flow order, which means we're
                     // as optimistic as possible
-                    valve_index[n].flow
-                        * (node
-                            .time_left
-                            .saturating_sub((1 + u32::try_from(i).unwrap()) * 2))
+                    self.network.flows[v] * node.time_left.saturating_sub(i * 2)
                 })
-                .sum::<u32>();
-
-        if upper_bound < best.release {
-            // println!(
-            //     "{}current best release {}, remaining upper bound is {}, bailing out",
-            //     " ".repeat(node.time_left as usize),
-            //     best.release,
-            //     upper_bound
-            // );
-            //bails += 1;
-            continue;
+                .sum::<u32>()
+    }
+}
+
+// a start without a flow isn't in any valve set, one with a flow takes a minute to open
+// like every other valve, so `valves` is left as it is
+fn start_node(network: &Network, valves: u64, minutes: u32) -> Node {
+    Node {
+        valve: network.start,
+        time_left: minutes,
+        release: 0,
+        valves_left: valves,
+        opened: 0,
+    }
+}
+
+// the best single route opening some of `valves`
+fn search(network: &Network, valves: u64, minutes: u32) -> (u32, Vec<usize>) {
+    let (release, path) = dfs_bound(
+        &Opening { network },
+        start_node(network, valves, minutes),
+    );
+    (release, path.iter().map(|node| node.valve).collect())
+}
+
+// the tables `best_releases` and `split_valves` make have an entry for every subset of the
+// valves with a flow, so there can't be many of them when there's more than one agent
+const MAX_WITH_FLOW: usize = 24;
+
+// the most one agent can release opening any subset of the valves, indexed by the subset's
+// bits. walks every route once and then lets every subset inherit from its own subsets
+fn best_releases(network: &Network, minutes: u32) -> color_eyre::Result<Vec<u32>> {
+    if network.with_flow > MAX_WITH_FLOW {
+        return Err(eyre!(
+            "{} valves with a flow, the subset tables only fit {MAX_WITH_FLOW}",
+            network.with_flow
+        ));
+    }
+
+    let opening = Opening { network };
+    let mut best = vec![0; 1 << network.with_flow];
+
+    let mut frontier = vec![start_node(network, network.all(), minutes)];
+    while let Some(node) = frontier.pop() {
+        let opened = usize::try_from(node.opened).unwrap();
+        best[opened] = best[opened].max(node.release);
+        opening.successors(&node, &mut frontier);
+    }
+
+    for bit in 0..network.with_flow {
+        for subset in 0..best.len() {
+            if subset & 1 << bit != 0 {
+                best[subset] = best[subset].max(best[subset ^ 1 << bit]);
+            }
         }
+    }
 
-        frontier.extend(node.valves_left.iter().enumerate().filter_map(|(i, n)| {
-            let shortest_path = shortest_paths[&(node.valve, *n)];
-            if let Some(new_time_left) = node.time_left.checked_sub(shortest_path + 1) {
-                let new_path = node.path.iter().chain([&node.valve]).copied().collect();
+    Ok(best)
+}
 
-                let mut new_valves_left = Vec::with_capacity(node.valves_left.len() - 1);
-                for (j, v) in node.valves_left.iter().enumerate() {
-                    if j != i {
-                        new_valves_left.push(*v);
-                    }
+// splits all the valves between `agents` disjoint subsets with the best total release,
+// returns the total and every agent's subset
+fn split_valves(best: &[u32], agents: usize) -> (u32, Vec<usize>) {
+    assert!(agents > 0, "someone has to open the valves");
+
+    // the best `agents` can do with a subset and what the last agent takes out of it
+    let mut total = best.to_vec();
+    let mut taken = Vec::new();
+    for _agent in 1..agents {
+        let mut last_taken = vec![0; best.len()];
+        let mut new_total = vec![0; best.len()];
+        for subset in 0..best.len() {
+            // every subset of `subset`, down to the empty one
+            let mut mine = subset;
+            loop {
+                let release = best[mine] + total[subset ^ mine];
+                if release > new_total[subset] {
+                    (new_total[subset], last_taken[subset]) = (release, mine);
                 }
-
-                Some(Node {
-                    valve: *n,
-                    time_left: new_time_left,
-                    release: node.release + valve_index[n].flow * new_time_left,
-                    path: new_path,
-                    valves_left: new_valves_left,
-                })
-            } else {
-                None
+                if mine == 0 {
+                    break;
+                }
+                mine = (mine - 1) & subset;
             }
-        }));
+        }
+        total = new_total;
+        taken.push(last_taken);
     }
 
-    //println!("{nodes} nodes, {bails} bails");
+    let mut left = best.len() - 1;
+    let mut subsets = Vec::with_capacity(agents);
+    for last_taken in taken.iter().rev() {
+        subsets.push(last_taken[left]);
+        left ^= last_taken[left];
+    }
+    subsets.push(left);
 
-    let mut full_path = best.path.clone();
-    full_path.push(best.valve);
-    (best.release, full_path)
+    (total[best.len() - 1], subsets)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let all_valves = input
-        .lines()
-        .map(|l| Valve::parse(l).unwrap().1)
-        .collect::<Vec<_>>();
+// who opens the valves, from where and for how long
+#[derive(Debug, Clone, Copy)]
+pub struct Plan {
+    start: ValveName,
+    minutes: u32,
+    agents: usize,
+}
 
-    let valve_index = all_valves
-        .iter()
-        .map(|v| (v.name, (*v).clone()))
-        .collect::<HashMap<_, Valve>>();
+impl Plan {
+    pub fn new(start: &str, minutes: u32, agents: usize) -> color_eyre::Result<Self> {
+        if agents == 0 {
+            return Err(eyre!("someone has to open the valves"));
+        }
+        Ok(Plan {
+            start: parse_all(start, parse_valve_name)?,
+            minutes,
+            agents,
+        })
+    }
 
-    let nonzero_valves = all_valves
-        .iter()
-        .filter(|v| v.flow > 0)
-        .map(|v| v.name)
-        .collect::<Vec<_>>();
+    // what the puzzle asks for, in part 2 4 minutes go on teaching the elephant
+    pub(crate) fn for_part(part: DayPart) -> color_eyre::Result<Self> {
+        match part {
+            DayPart::First => Plan::new("AA", 30, 1),
+            DayPart::Second => Plan::new("AA", 26, 2),
+        }
+    }
+}
+
+// "START,MINUTES,AGENTS", part 2 is "AA,26,2"
+impl FromStr for Plan {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> color_eyre::Result<Self> {
+        let (start, minutes_s, agents_s) = s
+            .split(',')
+            .collect_tuple()
+            .ok_or_else(|| eyre!("expected a plan like AA,26,2, got {s:?}"))?;
+        let minutes = minutes_s
+            .parse()
+            .wrap_err_with(|| format!("can't parse {minutes_s:?} into minutes"))?;
+        let agents = agents_s
+            .parse()
+            .wrap_err_with(|| format!("can't parse {agents_s:?} into agents"))?;
+        Plan::new(start, minutes, agents)
+    }
+}
+
+// the best total release and the valves each agent opens
+fn best_split(network: &Network, plan: Plan) -> color_eyre::Result<(u32, Vec<u64>)> {
+    if plan.agents == 1 {
+        // nothing to split, and branch and bound gets there without walking every route
+        let (release, _) = search(network, network.all(), plan.minutes);
+        Ok((release, vec![network.all()]))
+    } else {
+        let (release, subsets) =
+            split_valves(&best_releases(network, plan.minutes)?, plan.agents);
+        Ok((
+            release,
+            subsets.into_iter().map(|s| u64::try_from(s).unwrap()).collect(),
+        ))
+    }
+}
 
-    let shortest_paths = nonzero_valves
+// the order every agent opens its valves in, starting with the start
+fn routes(network: &Network, plan: Plan, subsets: &[u64]) -> Vec<Vec<usize>> {
+    subsets
         .iter()
-        // need to add AA back to find paths from the start node
-        .chain(&[ValveName::new("AA")])
-        .permutations(2)
-        .flat_map(|perm| {
-            let (va, vb) = (*perm[0], *perm[1]);
-            let d = shortest_path(&valve_index, va, vb, &mut HashSet::new()).unwrap();
-            vec![((va, vb), d), ((vb, va), d)].into_iter()
-        })
-        .collect::<HashMap<_, _>>();
+        .map(|subset| search(network, *subset, plan.minutes).1)
+        .collect()
+}
 
-    let now = Instant::now();
+fn agent_name(agent: usize, agents: usize) -> String {
+    match (agent, agents) {
+        (0, _) => "you".to_string(),
+        (1, 2) => "the elephant".to_string(),
+        (agent, _) => format!("elephant {agent}"),
+    }
+}
 
-    let result = search(
-        &valve_index,
-        &nonzero_valves,
-        &shortest_paths,
-        ValveName::new("AA"),
-        30,
-    );
+pub fn max_release(input: &str, plan: Plan) -> color_eyre::Result<u32> {
+    let all_valves = parse_lines(input, |l| parse_all(l, Valve::parse))?;
+    mark_parsed();
 
-    println!("{}", now.elapsed().as_secs_f32());
+    let network = Network::new(&all_valves, plan.start)?;
+    let (release, subsets) = best_split(&network, plan)?;
+
+    if log_enabled!(Level::Debug) {
+        for (agent, route) in routes(&network, plan, &subsets).iter().enumerate() {
+            debug!(
+                "{}: {}",
+                agent_name(agent, plan.agents),
+                route.iter().map(|v| network.names[*v]).join(" -> ")
+            );
+        }
+    }
 
-    Ok(result.0.to_string())
+    Ok(release)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let all_valves = input
-        .lines()
-        .map(|l| Valve::parse(l).unwrap().1)
-        .collect::<Vec<_>>();
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Action {
+    Move(ValveName),
+    Open(ValveName),
+    Wait,
+}
 
-    let valve_index = all_valves
-        .iter()
-        .map(|v| (v.name, (*v).clone()))
-        .collect::<HashMap<_, Valve>>();
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Move(valve) => write!(f, "move to {valve}"),
+            Action::Open(valve) => write!(f, "open {valve}"),
+            Action::Wait => write!(f, "wait"),
+        }
+    }
+}
+
+// what happens during one minute
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Minute {
+    // the valves that are open when it starts
+    open: Vec<ValveName>,
+    releasing: u32,
+    // in total, by the end of it
+    released: u32,
+    // one for every agent
+    actions: Vec<Action>,
+}
 
-    let interesting_valves = all_valves
+// every agent walking its route one tunnel at a time
+fn schedule(network: &Network, plan: Plan, routes: &[Vec<usize>]) -> Vec<Minute> {
+    let minutes = usize::try_from(plan.minutes).unwrap();
+    let actions = routes
         .iter()
-        .filter(|v| v.flow > 0)
-        .map(|v| v.name)
+        .map(|route| {
+            let mut actions = Vec::with_capacity(minutes);
+            for (from, to) in route.iter().tuple_windows() {
+                let path = network.path(*from, *to);
+                actions.extend(path.iter().map(|v| Action::Move(network.names[*v])));
+                actions.push(Action::Open(network.names[*to]));
+            }
+            actions.resize(minutes, Action::Wait);
+            actions
+        })
         .collect::<Vec<_>>();
 
-    let shortest_paths = interesting_valves
+    let flows = network
+        .names
         .iter()
-        // need to add AA back to find paths from the start node
-        .chain(&[ValveName::new("AA")])
-        .permutations(2)
-        .flat_map(|perm| {
-            let (va, vb) = (*perm[0], *perm[1]);
-            let d = shortest_path(&valve_index, va, vb, &mut HashSet::new()).unwrap();
-            vec![((va, vb), d), ((vb, va), d)].into_iter()
-        })
+        .copied()
+        .zip(network.flows.iter().copied())
         .collect::<HashMap<_, _>>();
+    let mut open = Vec::new();
+    let mut released = 0;
+    (0..minutes)
+        .map(|i| {
+            let releasing = open.iter().map(|v| flows[v]).sum();
+            released += releasing;
+            let minute = Minute {
+                open: open.clone(),
+                releasing,
+                released,
+                actions: actions.iter().map(|a| a[i]).collect(),
+            };
+            open.extend(minute.actions.iter().filter_map(|a| match a {
+                Action::Open(valve) => Some(*valve),
+                _ => None,
+            }));
+            minute
+        })
+        .collect()
+}
 
-    // let mut best_paths = None;
-    let best_release = (1..=(interesting_valves.len() / 2 + 1))
-        .flat_map(|n_mine| interesting_valves.iter().copied().combinations(n_mine))
-        .par_bridge()
-        .fold(
-            || 0,
-            |acc, my_valves| {
-                let elephant_valves = interesting_valves
+// how a part's answer comes about
+struct Explanation {
+    part: DayPart,
+    plan: Plan,
+    release: u32,
+    routes: Vec<Vec<ValveName>>,
+    schedule: Vec<Minute>,
+}
+
+fn explain(input: &str) -> color_eyre::Result<Vec<Explanation>> {
+    let all_valves = parse_lines(input, |l| parse_all(l, Valve::parse))?;
+
+    DayPart::BOTH
+        .into_iter()
+        .map(|part| {
+            let plan = Plan::for_part(part)?;
+            let network = Network::new(&all_valves, plan.start)?;
+            let (release, subsets) = best_split(&network, plan)?;
+            let routes = routes(&network, plan, &subsets);
+            Ok(Explanation {
+                part,
+                plan,
+                release,
+                schedule: schedule(&network, plan, &routes),
+                routes: routes
                     .iter()
-                    .filter(|v| !my_valves.contains(v))
-                    .copied()
-                    .collect::<Vec<_>>();
-
-                let (my_release, _my_path) = search(
-                    &valve_index,
-                    &my_valves,
-                    &shortest_paths,
-                    ValveName::new("AA"),
-                    26,
-                );
-                let (elephant_release, _elephant_path) = search(
-                    &valve_index,
-                    &elephant_valves,
-                    &shortest_paths,
-                    ValveName::new("AA"),
-                    26,
-                );
-
-                acc.max(my_release + elephant_release)
-            },
+                    .map(|route| route.iter().map(|v| network.names[*v]).collect())
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn render_schedule(input: &str) -> color_eyre::Result<Rendered> {
+    let mut out = String::new();
+    for e in explain(input)? {
+        writeln!(
+            out,
+            "part {}: {} released in {} minutes from {}",
+            e.part, e.release, e.plan.minutes, e.plan.start
+        )?;
+        for (agent, route) in e.routes.iter().enumerate() {
+            writeln!(
+                out,
+                "{}: {}",
+                agent_name(agent, e.plan.agents),
+                route.iter().join(" -> ")
+            )?;
+        }
+        for (i, minute) in e.schedule.iter().enumerate() {
+            write!(
+                out,
+                "minute {:2}: releasing {:3}, {:4} so far",
+                i + 1,
+                minute.releasing,
+                minute.released
+            )?;
+            for (agent, action) in minute.actions.iter().enumerate() {
+                write!(out, " | {}: {action}", agent_name(agent, e.plan.agents))?;
+            }
+            writeln!(out)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(Rendered::Text(out.trim_end().to_string()))
+}
+
+fn json_valves(valves: &[ValveName]) -> String {
+    format!(
+        "[{}]",
+        valves.iter().map(|v| format!("\"{}\"", v.code())).join(", ")
+    )
+}
+
+fn json_action(action: Action) -> String {
+    match action {
+        Action::Move(valve) => format!(r#"{{"action": "move", "valve": "{}"}}"#, valve.code()),
+        Action::Open(valve) => format!(r#"{{"action": "open", "valve": "{}"}}"#, valve.code()),
+        Action::Wait => r#"{"action": "wait"}"#.to_string(),
+    }
+}
+
+// the same as `render_schedule`, for checking plans with other tools
+fn render_schedule_json(input: &str) -> color_eyre::Result<Rendered> {
+    let explanations = explain(input)?;
+    let parts = explanations.iter().map(|e| {
+        let routes = e.routes.iter().map(|r| json_valves(r)).join(", ");
+        let schedule = e.schedule.iter().enumerate().map(|(i, m)| {
+            format!(
+                "    {{\"minute\": {}, \"open\": {}, \"releasing\": {}, \"released\": {}, \
+                 \"actions\": [{}]}}",
+                i + 1,
+                json_valves(&m.open),
+                m.releasing,
+                m.released,
+                m.actions.iter().copied().map(json_action).join(", ")
+            )
+        });
+        format!(
+            "  {{\"part\": {}, \"start\": \"{}\", \"minutes\": {}, \"agents\": {}, \
+             \"released\": {}, \"routes\": [{routes}], \"schedule\": [\n{}\n  ]}}",
+            e.part,
+            e.plan.start.code(),
+            e.plan.minutes,
+            e.plan.agents,
+            e.release,
+            schedule.collect::<Vec<_>>().join(",\n")
+        )
+    });
+    Ok(Rendered::Text(format!(
+        "[\n{}\n]",
+        parts.collect::<Vec<_>>().join(",\n")
+    )))
+}
+
+// every agent's route gets its own colour, going round if there are more agents
+const ROUTE_COLOURS: [&str; 4] = ["red", "blue", "darkgreen", "orange"];
+
+// the valves with a flow and the start as a Graphviz graph, with the tunnels between them
+// boiled down to their lengths and the routes drawn over them
+fn dot(network: &Network, routes: &[Vec<usize>]) -> String {
+    let mut nodes = (0..network.with_flow).collect::<Vec<_>>();
+    if network.start >= network.with_flow {
+        nodes.push(network.start);
+    }
+    let distance = |a: usize, b: usize| network.distances[(a, b)];
+
+    // only the ones that don't go past another valve, the rest follow from them
+    let mut edges = BTreeMap::new();
+    for (a, b) in nodes.iter().copied().tuple_combinations() {
+        let d = distance(a, b);
+        if nodes
+            .iter()
+            .all(|w| [a, b].contains(w) || distance(a, *w).saturating_add(distance(*w, b)) > d)
+        {
+            edges.insert((a, b), vec![format!("label={d}")]);
+        }
+    }
+    for (agent, route) in routes.iter().enumerate() {
+        let colour = ROUTE_COLOURS[agent % ROUTE_COLOURS.len()];
+        // opening a start with a flow isn't a leg
+        for (from, to) in route.iter().copied().tuple_windows().filter(|(a, b)| a != b) {
+            // legs going past other valves don't have an edge yet
+            let attributes = edges.entry((from.min(to), from.max(to))).or_insert_with(|| {
+                let d = distance(from, to);
+                vec![format!("label={d}"), "style=dashed".to_string()]
+            });
+            attributes.push(format!("color={colour}, penwidth=3"));
+            attributes.push(format!("dir={}", if from < to { "forward" } else { "back" }));
+        }
+    }
+
+    let mut out = String::from("graph day16 {\n");
+    for v in nodes {
+        let name = network.names[v].code();
+        let mut attributes = format!("label=\"{name}\\n{}\"", network.flows[v]);
+        if v == network.start {
+            attributes.push_str(", shape=doublecircle");
+        }
+        if network.flows[v] == 0 {
+            attributes.push_str(", style=filled, fillcolor=lightgrey");
+        }
+        writeln!(out, "  {name} [{attributes}];").unwrap();
+    }
+    for ((a, b), attributes) in edges {
+        writeln!(
+            out,
+            "  {} -- {} [{}];",
+            network.names[a].code(),
+            network.names[b].code(),
+            attributes.join(", ")
         )
-        .max()
         .unwrap();
+    }
+    out.push('}');
+    out
+}
+
+fn render_dot(input: &str, plan: Plan) -> color_eyre::Result<Rendered> {
+    let all_valves = parse_lines(input, |l| parse_all(l, Valve::parse))?;
+    let network = Network::new(&all_valves, plan.start)?;
+    let (_, subsets) = best_split(&network, plan)?;
+    Ok(Rendered::Text(dot(&network, &routes(&network, plan, &subsets))))
+}
 
-    Ok((best_release).to_string())
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    Ok(max_release(input, Plan::for_part(DayPart::First)?)?.into())
+}
+
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    Ok(max_release(input, Plan::for_part(DayPart::Second)?)?.into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 16,
     part1: solve1,
     part2: solve2,
+    renders: &[
+        Render {
+            name: "schedule",
+            help: "what everyone does every minute for the best release",
+            render: render_schedule,
+        },
+        Render {
+            name: "schedule-json",
+            help: "the schedule as JSON",
+            render: render_schedule_json,
+        },
+        Render {
+            name: "dot",
+            help: "a Graphviz graph of the valves with a flow and the route opening them",
+            render: |input| render_dot(input, Plan::for_part(DayPart::First)?),
+        },
+        Render {
+            name: "dot-elephant",
+            help: "the same graph with the routes for you and the elephant",
+            render: |input| render_dot(input, Plan::for_part(DayPart::Second)?),
+        },
+    ],
 };
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use pretty_assertions::assert_eq;
+
+    use crate::day16::{
+        best_split, dot, explain, max_release, routes, split_valves, Action, Network, Plan,
+        Valve, ValveName,
+    };
+    use crate::daylib::DayPart;
+    use crate::shared::{parse_all, parse_lines};
+
+    const INPUT: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II
+";
+
+    #[test]
+    fn split_valves_test() {
+        // valve 0 alone releases 5, valve 1 alone 7 and both together 9
+        let best = [0, 5, 7, 9];
+
+        assert_eq!(split_valves(&best, 1), (9, vec![3]));
+        assert_eq!(split_valves(&best, 2), (12, vec![2, 1]));
+        assert_eq!(split_valves(&best, 3).0, 12);
+    }
+
+    #[test]
+    fn explain_test() {
+        let explanations = explain(INPUT).unwrap();
+
+        for (e, release) in explanations.iter().zip([1651, 1707]) {
+            assert_eq!(e.release, release);
+            assert_eq!(e.schedule.len(), usize::try_from(e.plan.minutes).unwrap());
+            assert_eq!(e.schedule.last().unwrap().released, release);
+        }
+
+        // the same route as in the puzzle description
+        let alone = &explanations[0];
+        assert_eq!(
+            alone.schedule[..3].iter().map(|m| m.actions[0]).collect::<Vec<_>>(),
+            [
+                Action::Move(ValveName::new("DD")),
+                Action::Open(ValveName::new("DD")),
+                Action::Move(ValveName::new("CC")),
+            ]
+        );
+        assert_eq!(alone.schedule[2].releasing, 20);
+    }
+
+    #[test]
+    fn network_test() {
+        let valves = parse_lines(INPUT, |l| parse_all(l, Valve::parse)).unwrap();
+        let network = Network::new(&valves, ValveName::new("AA")).unwrap();
+
+        // highest flow first, then the ones without a flow in input order
+        assert_eq!(network.names[..2], [ValveName::new("HH"), ValveName::new("JJ")]);
+        assert_eq!(network.names[network.start], ValveName::new("AA"));
+        assert_eq!((network.with_flow, network.all()), (6, 0b11_1111));
+        assert_eq!(Network::valves(0b10_0101).collect::<Vec<_>>(), [0, 2, 5]);
+
+        let path = network.path(network.start, 0);
+        assert_eq!(network.distances[(network.start, 0)], 5);
+        assert_eq!(
+            path.iter().map(|v| network.names[*v]).collect::<Vec<_>>(),
+            ["DD", "EE", "FF", "GG", "HH"].map(ValveName::new)
+        );
+    }
+
+    #[test]
+    fn dot_test() {
+        let valves = parse_lines(INPUT, |l| parse_all(l, Valve::parse)).unwrap();
+        let network = Network::new(&valves, ValveName::new("AA")).unwrap();
+        let alone = Plan::for_part(DayPart::First).unwrap();
+        let (_, subsets) = best_split(&network, alone).unwrap();
+        let dot = dot(&network, &routes(&network, alone, &subsets));
+
+        // valves without a flow only show up as distances
+        assert!(!dot.contains("FF"));
+        assert!(dot.contains("  AA [label=\"AA\\n0\", shape=doublecircle"));
+        // the first leg of the route
+        assert!(dot.contains("  DD -- AA [label=1, color=red, penwidth=3, dir=back];"));
+        // HH is only reachable through EE
+        assert!(dot.contains("  HH -- EE [label=3, "));
+        assert!(!dot.contains("HH -- DD"));
+    }
+
+    // the most one agent can release opening any of `left`, trying every order
+    fn brute_force_route(network: &Network, at: usize, time_left: u32, left: u64) -> u32 {
+        Network::valves(left)
+            .filter_map(|v| {
+                let time_left = time_left.checked_sub(network.distances[(at, v)] + 1)?;
+                Some(
+                    network.flows[v] * time_left
+                        + brute_force_route(network, v, time_left, left & !(1 << v)),
+                )
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // every way of handing the valves out to the agents
+    fn brute_force(network: &Network, minutes: u32, agents: usize) -> u32 {
+        let assignments = agents.pow(u32::try_from(network.with_flow).unwrap());
+        (0..assignments)
+            .map(|mut assignment| {
+                let mut subsets = vec![0; agents];
+                for v in 0..network.with_flow {
+                    subsets[assignment % agents] |= 1 << v;
+                    assignment /= agents;
+                }
+                subsets
+                    .iter()
+                    .map(|s| brute_force_route(network, network.start, minutes, *s))
+                    .sum::<u32>()
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn plan_test() {
+        let valves = parse_lines(INPUT, |l| parse_all(l, Valve::parse)).unwrap();
+
+        // DD and JJ have a flow and have to be opened like any other valve, FF doesn't
+        for (start, minutes, agents) in [
+            ("AA", 30, 1),
+            ("AA", 26, 2),
+            ("AA", 20, 1),
+            ("AA", 12, 2),
+            ("AA", 26, 3),
+            ("DD", 30, 1),
+            ("DD", 26, 2),
+            ("JJ", 26, 2),
+            ("FF", 20, 3),
+        ] {
+            let network = Network::new(&valves, ValveName::new(start)).unwrap();
+            let plan = Plan::new(start, minutes, agents).unwrap();
+            assert_eq!(
+                max_release(INPUT, plan).unwrap(),
+                brute_force(&network, minutes, agents),
+                "from {start} in {minutes} minutes with {agents} agents"
+            );
+        }
+
+        assert!(Plan::new("AA", 30, 0).is_err());
+        assert!(Plan::new("A", 30, 1).is_err());
+        assert!(matches!(
+            "DD,26,3".parse::<Plan>(),
+            Ok(Plan { minutes: 26, agents: 3, .. })
+        ));
+        for s in ["AA,26", "AA,26,2,1", "AA,-1,2", "AA,26,none", "AA,26,0", "AAA,26,1"] {
+            assert!(s.parse::<Plan>().is_err(), "{s}");
+        }
+        assert!(max_release(INPUT, Plan::new("ZZ", 30, 1).unwrap()).is_err());
+    }
+
+    // valves in a line, the first `with_flow` of them with a flow
+    fn line(len: usize, with_flow: usize) -> String {
+        let name = |i: usize| {
+            let [a, b] = [i / 26, i % 26].map(|c| char::from(b'A' + u8::try_from(c).unwrap()));
+            format!("{a}{b}")
+        };
+        (0..len)
+            .map(|i| {
+                let flow = usize::from(i < with_flow);
+                let tunnels = [i.checked_sub(1), Some(i + 1).filter(|n| *n < len)]
+                    .into_iter()
+                    .flatten()
+                    .map(name)
+                    .join(", ");
+                format!(
+                    "Valve {} has flow rate={flow}; tunnels lead to valves {tunnels}\n",
+                    name(i)
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn limits_test() {
+        // a start without a flow at an index past 64
+        let input = line(80, 2);
+        let plan = Plan::new("DB", 10, 2).unwrap();
+        assert_eq!(max_release(&input, plan).unwrap(), 0);
+        let plan = Plan::new("AC", 10, 2).unwrap();
+        assert_eq!(max_release(&input, plan).unwrap(), 8 + 7);
+
+        // too many valves with a flow for the subset tables, one agent doesn't need them
+        let input = line(30, 30);
+        assert!(max_release(&input, Plan::new("AA", 30, 2).unwrap()).is_err());
+        let plan = Plan::new("AA", 30, 1).unwrap();
+        assert_eq!(max_release(&input, plan).unwrap(), (1..=29).step_by(2).sum::<u32>());
+
+        // more than fit in a valve set
+        let input = line(70, 70);
+        assert!(max_release(&input, Plan::new("AA", 30, 1).unwrap()).is_err());
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-use std::iter;
 use std::ops::{Index, IndexMut};
 
 use color_eyre::eyre::eyre;
 use itertools::Itertools;
+use log::debug;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{find_cycle, Grid, ParseError};
 
 const WIDTH: usize = 7;
 
@@ -13,14 +13,14 @@ const WIDTH: usize = 7;
 // to fall down the tower and affect lower levels, but hey ho if it works on test data
 const MAX_DEPTH: usize = 100;
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 enum Direction {
     Left,
     Right,
 }
 
 impl Direction {
-    fn to_dx(&self) -> isize {
+    fn to_dx(self) -> isize {
         match self {
             Direction::Left => -1,
             Direction::Right => 1,
@@ -49,20 +49,32 @@ impl TryFrom<char> for Direction {
     }
 }
 
+fn parse(input: &str) -> Result<Vec<Direction>, ParseError> {
+    let jets = input.trim_end();
+    if jets.is_empty() {
+        return Err(ParseError::new(input, jets, "expected at least one jet"));
+    }
+
+    jets.char_indices()
+        .map(|(i, c)| {
+            Direction::try_from(c).map_err(|e| ParseError::new(input, &jets[i..], e.to_string()))
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 struct Space {
-    occupied: Vec<Content>,
+    // bottom row first, so the container grows and shrinks at the end
+    occupied: Grid<Content>,
     contents_height: usize,
-    container_height: usize,
     rows_removed: usize,
 }
 
 impl Space {
     fn new() -> Self {
         Self {
-            occupied: vec![],
+            occupied: Grid::new(WIDTH, 0, Content::Empty),
             contents_height: 0,
-            container_height: 0,
             rows_removed: 0,
         }
     }
@@ -73,29 +85,20 @@ impl Space {
         }
         let rows_to_remove = self.contents_height - MAX_DEPTH;
 
-        drop(self.occupied.drain(..(rows_to_remove * WIDTH)));
+        self.occupied.remove_rows(0..rows_to_remove);
         self.contents_height -= rows_to_remove;
-        self.container_height -= rows_to_remove;
         self.rows_removed += rows_to_remove;
     }
 
     fn adjust_size(&mut self, new_shape: &Shape) {
-        let new_container_height = self.contents_height + 3 + new_shape.height;
-        if new_container_height < self.container_height {
-            self.occupied.truncate(new_container_height * WIDTH);
-        } else {
-            self.occupied.extend(
-                iter::repeat(Content::Empty)
-                    .take((new_container_height - self.container_height) * WIDTH),
-            );
-        };
-        self.container_height = new_container_height;
+        self.occupied
+            .resize_rows(self.contents_height + 3 + new_shape.height, Content::Empty);
 
         self.truncate();
     }
 
     fn imprint(&mut self, shape: &Shape, (offset_x, offset_y): (usize, usize)) {
-        self.contents_height = self.contents_height.max((self.container_height) - offset_y);
+        self.contents_height = self.contents_height.max(self.occupied.height() - offset_y);
 
         shape
             .points
@@ -104,7 +107,7 @@ impl Space {
     }
 
     #[allow(dead_code)]
-    fn print(&self, falling: Option<(&Shape, (usize, usize))>) {
+    fn format(&self, falling: Option<(&Shape, (usize, usize))>) -> String {
         let mut tmp_space = self.clone();
 
         if let Some((shape, (offset_x, offset_y))) = falling {
@@ -113,24 +116,27 @@ impl Space {
             }
         }
 
-        for y in (0..(tmp_space.occupied.len() / WIDTH)).rev() {
-            println!(
-                "{}\t|{}|",
-                tmp_space.container_height - y - 1,
-                tmp_space.occupied[(y * WIDTH)..((y + 1) * WIDTH)]
-                    .iter()
-                    .map(|x| {
-                        match x {
-                            Content::Empty => '.',
-                            Content::Stopped => '#',
-                            Content::Falling => '@',
-                        }
-                    })
-                    .collect::<String>()
-            );
-        }
-
-        println!();
+        let formatted = tmp_space
+            .occupied
+            .rows()
+            .rev()
+            .enumerate()
+            .map(|(y, row)| {
+                format!(
+                    "{y}\t|{}|",
+                    row.iter()
+                        .map(|x| {
+                            match x {
+                                Content::Empty => '.',
+                                Content::Stopped => '#',
+                                Content::Falling => '@',
+                            }
+                        })
+                        .collect::<String>()
+                )
+            })
+            .join("\n");
+        formatted
     }
 }
 
@@ -138,17 +144,14 @@ impl Index<(usize, usize)> for Space {
     type Output = Content;
 
     fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        self.occupied
-            .get(self.container_height.checked_sub(y + 1).unwrap() * WIDTH + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+        &self.occupied[(x, self.occupied.height().checked_sub(y + 1).unwrap())]
     }
 }
 
 impl IndexMut<(usize, usize)> for Space {
     fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        self.occupied
-            .get_mut(self.container_height.checked_sub(y + 1).unwrap() * WIDTH + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+        let row = self.occupied.height().checked_sub(y + 1).unwrap();
+        &mut self.occupied[(x, row)]
     }
 }
 
@@ -176,7 +179,7 @@ impl Shape {
         }
         self.points.iter().any(|(dx, dy)| {
             tentative_x as usize + dx > (WIDTH - 1)
-                || tentative_y + dy == space.occupied.len() / WIDTH
+                || tentative_y + dy == space.occupied.height()
                 || space[(tentative_x as usize + dx, tentative_y + dy)] != Content::Empty
         })
     }
@@ -192,164 +195,122 @@ fn get_shapes() -> Vec<Shape> {
     ]
 }
 
-#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
-fn simulate(
-    directions: impl Iterator<Item = Direction> + Clone,
-    shapes: impl Iterator<Item = Shape> + Clone,
-    n_rocks: usize,
-) -> Space {
-    let mut space = Space::new();
+#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+struct State {
+    rows: Vec<u8>,
+    jet_idx: usize,
+    shape_idx: usize,
+}
+
+// rocks falling one at a time, with the jets and shapes going round
+#[derive(Debug, Clone)]
+struct Tower {
+    space: Space,
+    jets: Vec<Direction>,
+    shapes: Vec<Shape>,
+    // the next ones to use
+    jet_idx: usize,
+    shape_idx: usize,
+}
+
+impl Tower {
+    fn new(jets: Vec<Direction>) -> Self {
+        Self {
+            space: Space::new(),
+            jets,
+            shapes: get_shapes(),
+            jet_idx: 0,
+            shape_idx: 0,
+        }
+    }
 
-    let mut directions = directions.cycle();
-    let mut shapes = shapes.cycle();
+    fn height(&self) -> usize {
+        self.space.contents_height + self.space.rows_removed
+    }
 
-    for _rock in 0..n_rocks {
-        let shape = shapes.next().unwrap();
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn drop_rock(&mut self) {
+        let shape = &self.shapes[self.shape_idx];
+        self.shape_idx = (self.shape_idx + 1) % self.shapes.len();
 
-        space.adjust_size(&shape);
+        self.space.adjust_size(shape);
 
         let mut offset_x = 2usize;
         let mut offset_y = 0usize;
         loop {
-            let jet_dx = directions.next().unwrap().to_dx();
+            let jet_dx = self.jets[self.jet_idx].to_dx();
+            self.jet_idx = (self.jet_idx + 1) % self.jets.len();
 
             let tentative_x = offset_x as isize + jet_dx;
-            if !shape.collides(&space, (tentative_x, offset_y)) {
+            if !shape.collides(&self.space, (tentative_x, offset_y)) {
                 assert!(tentative_x >= 0);
                 offset_x = tentative_x as usize;
             }
 
-            if shape.collides(&space, (offset_x as isize, offset_y + 1)) {
+            if shape.collides(&self.space, (offset_x as isize, offset_y + 1)) {
                 break;
             }
 
             offset_y += 1;
         }
 
-        space.imprint(&shape, (offset_x, offset_y));
+        self.space.imprint(shape, (offset_x, offset_y));
     }
 
-    space
-}
-
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let directions = input.chars().map(Direction::try_from).map(Result::unwrap);
-    let shapes = get_shapes().into_iter();
-
-    let space = simulate(directions, shapes, 2022);
-
-    Ok((space.contents_height + space.rows_removed).to_string())
-}
-
-#[derive(Debug, Hash, Eq, PartialEq, Clone)]
-struct State {
-    rows: Vec<u8>,
-    jet_idx: u8,
-    shape_idx: u8,
-}
-
-impl State {
-    #[allow(clippy::cast_possible_truncation)]
-    fn new(space: &Space, jet_idx: usize, shape_idx: usize) -> Self {
+    // everything that decides where the next rocks end up
+    fn state(&self) -> State {
         State {
-            rows: space
+            rows: self
+                .space
                 .occupied
-                .iter()
-                .chunks(WIDTH)
-                .into_iter()
+                .rows()
                 .map(|x| {
-                    x.fold(0u8, |acc, c| match c {
+                    x.iter().fold(0u8, |acc, c| match c {
                         Content::Empty => acc * 2,
                         Content::Stopped => acc * 2 + 1,
                         Content::Falling => unreachable!(),
                     })
                 })
                 .collect(),
-            jet_idx: jet_idx as u8,
-            shape_idx: shape_idx as u8,
+            jet_idx: self.jet_idx,
+            shape_idx: self.shape_idx,
         }
     }
 }
 
-#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
-fn find_cycle(
-    directions: impl Iterator<Item = Direction> + Clone,
-    shapes: impl Iterator<Item = Shape> + Clone,
-) -> (usize, usize) {
-    let mut space = Space::new();
-
-    let mut directions = directions.enumerate().cycle();
-    let mut shapes = shapes.enumerate().cycle();
-
-    let mut seen_states = HashMap::<State, usize>::new();
-
-    for rock in 0.. {
-        let (shape_idx, shape) = shapes.next().unwrap();
-
-        space.adjust_size(&shape);
-
-        let mut offset_x = 2usize;
-        let mut offset_y = 0usize;
-        let mut last_jet_idx;
-        loop {
-            let (jet_idx, jet) = directions.next().unwrap();
-            last_jet_idx = jet_idx;
-
-            let tentative_x = offset_x as isize + jet.to_dx();
-            if !shape.collides(&space, (tentative_x, offset_y)) {
-                assert!(tentative_x >= 0);
-                offset_x = tentative_x as usize;
-            }
-
-            if shape.collides(&space, (offset_x as isize, offset_y + 1)) {
-                break;
-            }
-
-            offset_y += 1;
-        }
-
-        space.imprint(&shape, (offset_x, offset_y));
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let mut tower = Tower::new(parse(input)?);
+    mark_parsed();
 
-        let new_state = State::new(&space, last_jet_idx, shape_idx);
-
-        if let Some(prev_rock) = seen_states.insert(new_state, rock) {
-            println!("found a repeat state after {rock} rocks, previous rock {prev_rock}, cycle length {}",
-                rock - prev_rock);
-            return (prev_rock, rock);
-        }
+    for _rock in 0..2022 {
+        tower.drop_rock();
     }
 
-    unreachable!()
+    Ok(tower.height().try_into()?)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let target_stones = 1_000_000_000_000;
-
-    let directions = input.chars().map(Direction::try_from).map(Result::unwrap);
-    let shapes = get_shapes().into_iter();
-
-    let (cycle_start, cycle_end) = find_cycle(directions.clone(), shapes.clone());
-
-    // -------
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let tower = Tower::new(parse(input)?);
+    mark_parsed();
 
-    let space0 = simulate(directions.clone(), shapes.clone(), cycle_start);
-    let space1 = simulate(directions.clone(), shapes.clone(), cycle_end);
-
-    let height_per_loop = (space1.rows_removed + space1.contents_height)
-        - (space0.rows_removed + space0.contents_height);
-    let num_loops = (target_stones - cycle_start) / (cycle_end - cycle_start);
-    let after_loop = (target_stones - cycle_start) % (cycle_end - cycle_start);
-
-    let unlooped_space = simulate(directions, shapes, cycle_end + after_loop);
-    let unlooped_height = unlooped_space.contents_height + unlooped_space.rows_removed;
-
-    Ok((unlooped_height + (num_loops - 1) * height_per_loop).to_string())
+    let target_stones = 1_000_000_000_000;
+    let cycle = find_cycle(
+        tower,
+        Tower::drop_rock,
+        Tower::state,
+        |tower| i64::try_from(tower.height()).unwrap(),
+    );
+    debug!(
+        "the tower repeats every {} rocks after the first {}, growing by {}",
+        cycle.period, cycle.prefix, cycle.delta
+    );
+
+    Ok(cycle.value_after(target_stones).into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 17,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,33 +1,10 @@
-use std::collections::VecDeque;
-use std::ops::{Index, IndexMut};
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::search::{bfs_distances, Search};
+use crate::shared::{parse_at, parse_lines, ParseError, SparseGrid};
 
-use crate::daylib::Day;
+type Cube = (isize, isize, isize);
 
-const GRID_SIZE: usize = 22;
-
-struct Grid {
-    d: [bool; GRID_SIZE * GRID_SIZE * GRID_SIZE],
-}
-
-impl Index<(usize, usize, usize)> for Grid {
-    type Output = bool;
-
-    fn index(&self, (x, y, z): (usize, usize, usize)) -> &Self::Output {
-        self.d
-            .get(x * GRID_SIZE * GRID_SIZE + y * GRID_SIZE + z)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y} z {z}"))
-    }
-}
-
-impl IndexMut<(usize, usize, usize)> for Grid {
-    fn index_mut(&mut self, (x, y, z): (usize, usize, usize)) -> &mut Self::Output {
-        self.d
-            .get_mut(x * GRID_SIZE * GRID_SIZE + y * GRID_SIZE + z)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y} z {z}"))
-    }
-}
-
-const DELTAS: [(isize, isize, isize); 6] = [
+const DELTAS: [Cube; 6] = [
     (0, 0, 1),
     (0, 0, -1),
     (0, 1, 0),
@@ -36,125 +13,95 @@ const DELTAS: [(isize, isize, isize); 6] = [
     (-1, 0, 0),
 ];
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let mut grid = Grid {
-        d: [false; GRID_SIZE * GRID_SIZE * GRID_SIZE],
+fn neighbours((x, y, z): Cube) -> impl Iterator<Item = Cube> {
+    DELTAS
+        .iter()
+        .map(move |(dx, dy, dz)| (x + dx, y + dy, z + dz))
+}
+
+fn parse_cube(l: &str) -> Result<Cube, ParseError> {
+    let mut it = l.split(',');
+    let (Some(x_s), Some(y_s), Some(z_s), None) = (it.next(), it.next(), it.next(), it.next())
+    else {
+        return Err(ParseError::new(l, l, "expected a cube like 2,2,2"));
     };
 
-    let cubes = input
-        .lines()
-        .map(|l| {
-            let mut it = l.split(',');
-            if let (Some(x_s), Some(y_s), Some(z_s), None) =
-                (it.next(), it.next(), it.next(), it.next())
-            {
-                (
-                    x_s.parse().unwrap(),
-                    y_s.parse().unwrap(),
-                    z_s.parse().unwrap(),
-                )
-            } else {
-                panic!("weird line {l}");
-            }
-        })
-        .collect::<Vec<(usize, usize, usize)>>();
+    Ok((parse_at(l, x_s)?, parse_at(l, y_s)?, parse_at(l, z_s)?))
+}
+
+fn parse(input: &str) -> Result<(Vec<Cube>, SparseGrid<Cube, bool>), ParseError> {
+    let cubes = parse_lines(input, parse_cube)?;
 
+    let mut grid = SparseGrid::new(false);
     for c in &cubes {
         grid[*c] = true;
     }
 
+    Ok((cubes, grid))
+}
+
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let (cubes, grid) = parse(input)?;
+    mark_parsed();
+
     Ok(cubes
         .iter()
-        .map(|(x, y, z)| {
-            6 - DELTAS
-                .iter()
-                .copied()
-                .map(|(dx, dy, dz)| {
-                    if let (Some(x2), Some(y2), Some(z2)) = (
-                        x.checked_add_signed(dx),
-                        y.checked_add_signed(dy),
-                        z.checked_add_signed(dz),
-                    ) {
-                        usize::from(grid[(x2, y2, z2)])
-                    } else {
-                        // negative coordinates
-                        0
-                    }
-                })
-                .sum::<usize>()
-        })
+        .map(|c| 6 - neighbours(*c).filter(|n| grid[*n]).count())
         .sum::<usize>()
-        .to_string())
+        .try_into()?)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let mut grid = Grid {
-        d: [false; GRID_SIZE * GRID_SIZE * GRID_SIZE],
-    };
-    let mut grid_seen = Grid {
-        d: [false; GRID_SIZE * GRID_SIZE * GRID_SIZE],
-    };
+// the air around the droplet, inside a box with a layer of air all around it
+struct Outside<'a> {
+    lava: &'a SparseGrid<Cube, bool>,
+    min: Cube,
+    max: Cube,
+}
 
-    let cubes = input
-        .lines()
-        .map(|l| {
-            let mut it = l.split(',');
-            if let (Some(x_s), Some(y_s), Some(z_s), None) =
-                (it.next(), it.next(), it.next(), it.next())
-            {
-                (
-                    // shift them a bit to BFS on the outside
-                    x_s.parse::<usize>().unwrap() + 1,
-                    y_s.parse::<usize>().unwrap() + 1,
-                    z_s.parse::<usize>().unwrap() + 1,
-                )
-            } else {
-                panic!("weird line {l}");
-            }
-        })
-        .collect::<Vec<(usize, usize, usize)>>();
+impl Search for Outside<'_> {
+    type State = Cube;
 
-    for c in &cubes {
-        grid[*c] = true;
+    fn successors(&self, cube: &Cube) -> Vec<(Cube, u32)> {
+        let (min, max) = (self.min, self.max);
+        neighbours(*cube)
+            .filter(|(x, y, z)| {
+                (min.0..=max.0).contains(x)
+                    && (min.1..=max.1).contains(y)
+                    && (min.2..=max.2).contains(z)
+            })
+            .filter(|c| !self.lava[*c])
+            .map(|c| (c, 1))
+            .collect()
     }
 
-    let mut q = VecDeque::<(usize, usize, usize)>::new();
-    q.push_back((0, 0, 0));
-
-    let mut surfaces = 0;
-
-    while let Some((x, y, z)) = q.pop_front() {
-        for neighbour in DELTAS
-            .iter()
-            .filter_map(|(dx, dy, dz)| {
-                if let (Some(x2), Some(y2), Some(z2)) = (
-                    x.checked_add_signed(*dx),
-                    y.checked_add_signed(*dy),
-                    z.checked_add_signed(*dz),
-                ) {
-                    Some((x2, y2, z2))
-                } else {
-                    None
-                }
-            })
-            .filter(|(x, y, z)| *x < GRID_SIZE && *y < GRID_SIZE && *z < GRID_SIZE)
-        {
-            if grid[neighbour] {
-                surfaces += 1;
-            } else if !grid_seen[neighbour] {
-                q.push_back(neighbour);
-                grid_seen[neighbour] = true;
-            }
-        }
+    fn is_goal(&self, _cube: &Cube) -> bool {
+        false
     }
+}
 
-    Ok(surfaces.to_string())
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let (_cubes, lava) = parse(input)?;
+    mark_parsed();
+
+    let Some((min, max)) = lava.bounds() else {
+        return Ok(0.into());
+    };
+    let outside = Outside {
+        lava: &lava,
+        min: (min.0 - 1, min.1 - 1, min.2 - 1),
+        max: (max.0 + 1, max.1 + 1, max.2 + 1),
+    };
+
+    Ok(bfs_distances(&outside, outside.min)
+        .keys()
+        .map(|air| neighbours(*air).filter(|c| lava[*c]).count())
+        .sum::<usize>()
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 18,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,14 +1,19 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::ops::{Index, IndexMut};
 
+use log::{debug, trace};
 use nom::{
     bytes::complete::tag,
     combinator::map,
     sequence::{preceded, terminated, tuple},
     IResult,
 };
+use rayon::prelude::*;
 
-use crate::daylib::Day;
-use crate::shared::parse_unum;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::search::{dfs_bound, BranchAndBound};
+use crate::shared::{parse_all, parse_lines, parse_unum};
 
 // obs is short for Obsidian
 #[derive(Debug, Copy, Clone)]
@@ -86,7 +91,7 @@ enum Resource {
     Geode,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, Hash, Eq, PartialEq)]
 struct PerResource {
     ore: usize,
     clay: usize,
@@ -120,18 +125,18 @@ impl IndexMut<Resource> for PerResource {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 struct SearchNode {
     stock: PerResource,
     robots: PerResource,
     time_left: usize,
 }
 
-fn add_production(mut stock: PerResource, robots: PerResource) -> PerResource {
+fn add_production(mut stock: PerResource, robots: PerResource, minutes: usize) -> PerResource {
     use Resource::{Clay, Geode, Obsidian, Ore};
 
     for r in [Geode, Obsidian, Clay, Ore] {
-        stock[r] += robots[r];
+        stock[r] += robots[r] * minutes;
     }
 
     stock
@@ -151,136 +156,178 @@ fn subtract_robot_cost(
     stock
 }
 
-#[allow(dead_code)]
-fn best_heuristic_loop(node: SearchNode, blueprint: Blueprint) -> usize {
-    use Resource::{Geode, Obsidian};
-
-    let mut geodes = node.stock[Geode];
-    let mut geode_robots = node.robots[Geode];
-    let mut obsidian_robots = node.robots[Obsidian];
-    let mut obsidian = node.stock[Obsidian];
-    for _ in 0..=node.time_left {
-        obsidian += obsidian_robots;
-        geodes += geode_robots;
-        if obsidian >= blueprint[Geode][Obsidian] {
-            obsidian = obsidian.checked_sub(blueprint[Geode][Obsidian]).unwrap();
-            geode_robots += 1;
+// as if ore was free and clay and obsidian robots only needed one resource each, so we can
+// build a clay robot every minute and the others whenever there's enough of what they need
+fn best_heuristic_relaxed(node: SearchNode, blueprint: Blueprint) -> usize {
+    use Resource::{Clay, Geode, Obsidian};
+
+    let (mut stock, mut robots) = (node.stock, node.robots);
+    for _ in 0..node.time_left {
+        let obsidian_robot = stock[Clay] >= blueprint[Obsidian][Clay];
+        let geode_robot = stock[Obsidian] >= blueprint[Geode][Obsidian];
+        stock = add_production(stock, robots, 1);
+
+        if obsidian_robot {
+            stock[Clay] -= blueprint[Obsidian][Clay];
+            robots[Obsidian] += 1;
+        }
+        if geode_robot {
+            stock[Obsidian] -= blueprint[Geode][Obsidian];
+            robots[Geode] += 1;
         }
-        obsidian_robots += 1;
+        robots[Clay] += 1;
     }
 
-    geodes
+    stock[Geode]
 }
 
-#[allow(dead_code)]
-fn best_heuristic_nikita(node: SearchNode, blueprint: Blueprint) -> usize {
-    use Resource::{Geode, Obsidian};
+const ALL_RESOURCES: [Resource; 4] = [
+    Resource::Ore,
+    Resource::Clay,
+    Resource::Obsidian,
+    Resource::Geode,
+];
 
-    let t = node.time_left;
-    let future_obsidian = t * (t + 1) / 2;
-    let obsidian_end = node.stock[Obsidian] + node.robots[Obsidian] * t + future_obsidian;
-    node.stock[Geode] + node.robots[Geode] * t + (obsidian_end / blueprint[Geode][Obsidian]) * t
+// one blueprint's search, remembering the nodes it has already been through
+struct Optimiser {
+    blueprint: Blueprint,
+    // we can only build one robot a minute, so there's no point in producing more of
+    // anything than the most expensive robot needs of it, except for geodes
+    max_robots: PerResource,
+    seen: RefCell<HashSet<SearchNode>>,
 }
 
-fn max_geodes(blueprint: Blueprint, time: usize) -> usize {
-    use Resource::{Clay, Geode, Obsidian, Ore};
-
-    const ALL_RESOURCES: [Resource; 4] = [Ore, Clay, Obsidian, Geode];
-
-    let mut start_robots = PerResource::default();
-    start_robots[Ore] += 1;
-
-    let mut frontier = vec![SearchNode {
-        stock: PerResource::default(),
-        robots: start_robots,
-        time_left: time,
-    }];
+impl Optimiser {
+    fn new(blueprint: Blueprint) -> Self {
+        let mut max_robots = PerResource {
+            geode: usize::MAX,
+            ..PerResource::default()
+        };
+        for robot in ALL_RESOURCES {
+            for r in [Resource::Ore, Resource::Clay, Resource::Obsidian] {
+                max_robots[r] = max_robots[r].max(blueprint[robot][r]);
+            }
+        }
 
-    let mut most_geodes = 0;
+        Optimiser {
+            blueprint,
+            max_robots,
+            seen: RefCell::default(),
+        }
+    }
 
-    println!("{blueprint:?}");
+    // how many minutes of production until we can afford `robot`, None if we don't
+    // produce something it needs at all
+    fn minutes_to_afford(&self, node: &SearchNode, robot: Resource) -> Option<usize> {
+        ALL_RESOURCES.iter().try_fold(0, |wait, r| {
+            let cost = self.blueprint[robot][*r];
+            let missing = cost.saturating_sub(node.stock[*r]);
+            match (missing, node.robots[*r]) {
+                (0, _) => Some(wait),
+                (_, 0) => None,
+                (missing, robots) => Some(wait.max((missing + robots - 1) / robots)),
+            }
+        })
+    }
+}
 
-    while let Some(node) = frontier.pop() {
-        // if iterations % 1_000_000 == 0 {
-        //     println!("{iterations} iterations, {trimmed} trimmed");
-        //     println!("{node:?}");
-        // }
+// jumps straight to building the next robot, whatever that takes of waiting for resources.
+// not building anything else is covered by the score, which counts geodes up to the end
+impl BranchAndBound for Optimiser {
+    type State = SearchNode;
+    type Score = usize;
 
-        if node.stock[Geode] > most_geodes {
-            most_geodes = node.stock[Geode];
-            println!("new best {most_geodes}; {node:?}");
+    fn successors(&self, node: &SearchNode, out: &mut Vec<SearchNode>) {
+        if !self.seen.borrow_mut().insert(*node) {
+            return;
         }
 
-        if node.time_left == 0 {
-            continue;
+        // geode robots are pushed last, so they're tried first
+        for robot in ALL_RESOURCES {
+            if node.robots[robot] >= self.max_robots[robot] {
+                continue;
+            }
+            let Some(wait) = self.minutes_to_afford(node, robot) else {
+                continue;
+            };
+            // building takes a minute, and a robot that's done at the very end doesn't
+            // produce anything
+            let minutes = wait + 1;
+            if minutes >= node.time_left {
+                continue;
+            }
+
+            let stock = add_production(node.stock, node.robots, minutes);
+            let mut robots = node.robots;
+            robots[robot] += 1;
+
+            out.push(SearchNode {
+                stock: subtract_robot_cost(stock, self.blueprint, robot),
+                robots,
+                time_left: node.time_left - minutes,
+            });
         }
+    }
 
-        if best_heuristic_loop(node, blueprint) <= most_geodes {
-            continue;
-        }
+    fn score(&self, node: &SearchNode) -> usize {
+        node.stock[Resource::Geode] + node.robots[Resource::Geode] * node.time_left
+    }
 
-        frontier.push(SearchNode {
-            stock: add_production(node.stock, node.robots),
-            time_left: node.time_left - 1,
-            ..node
-        });
-
-        ALL_RESOURCES
-            .iter()
-            .filter(|robot| {
-                ALL_RESOURCES
-                    .iter()
-                    .all(|r| blueprint[**robot][*r] <= node.stock[*r])
-            })
-            .for_each(|robot| {
-                let mut new_robots = node.robots;
-                new_robots[*robot] += 1;
-
-                frontier.push(SearchNode {
-                    stock: (add_production(
-                        subtract_robot_cost(node.stock, blueprint, *robot),
-                        node.robots,
-                    )),
-                    robots: new_robots,
-                    time_left: node.time_left - 1,
-                });
-            });
+    fn bound(&self, node: &SearchNode) -> usize {
+        best_heuristic_relaxed(*node, self.blueprint)
     }
+}
+
+fn max_geodes(blueprint: Blueprint, time: usize) -> usize {
+    let mut start_robots = PerResource::default();
+    start_robots[Resource::Ore] += 1;
+
+    debug!("{blueprint:?}");
+
+    let optimiser = Optimiser::new(blueprint);
+    let (most_geodes, path) = dfs_bound(
+        &optimiser,
+        SearchNode {
+            stock: PerResource::default(),
+            robots: start_robots,
+            time_left: time,
+        },
+    );
+    trace!(
+        "best {most_geodes} after {} nodes; {:?}",
+        optimiser.seen.borrow().len(),
+        path.last()
+    );
 
     most_geodes
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let blueprints = input
-        .lines()
-        .map(|l| Blueprint::parse(l).unwrap().1)
-        .collect::<Vec<_>>();
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let blueprints = parse_lines(input, |l| parse_all(l, Blueprint::parse))?;
+    mark_parsed();
 
     Ok(blueprints
-        .iter()
+        .par_iter()
         .map(|b| b.id * max_geodes(*b, 24))
         .sum::<usize>()
-        .to_string())
+        .try_into()?)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let blueprints = input
-        .lines()
-        .map(|l| Blueprint::parse(l).unwrap().1)
-        .collect::<Vec<_>>();
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let blueprints = parse_lines(input, |l| parse_all(l, Blueprint::parse))?;
+    mark_parsed();
 
     Ok(blueprints
-        .iter()
+        .par_iter()
         .take(3)
         .map(|b| max_geodes(*b, 32))
         .product::<usize>()
-        .to_string())
+        .try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 19,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,23 +1,21 @@
 use std::collections::VecDeque;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_at, parse_lines};
 
 #[derive(Debug, Copy, Clone)]
 struct Num(usize, i32);
 
 // it's a bit sloppy with casts but w/e
 #[allow(
-    clippy::unnecessary_wraps,
     clippy::cast_lossless,
     clippy::cast_possible_wrap,
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss
 )]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let original = input
-        .lines()
-        .map(|l| l.parse::<i32>().unwrap())
-        .collect::<Vec<_>>();
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let original = parse_lines(input, |l| parse_at::<i32>(l, l))?;
+    mark_parsed();
     let n = original.len();
     let mut mixed = original
         .iter()
@@ -54,7 +52,7 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
     //     (mixed[1000 % n].1, mixed[2000 % n].1, mixed[3000 % n].1)
     // );
 
-    Ok((mixed[1000 % n].1 + mixed[2000 % n].1 + mixed[3000 % n].1).to_string())
+    Ok((mixed[1000 % n].1 + mixed[2000 % n].1 + mixed[3000 % n].1).into())
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -64,17 +62,14 @@ struct Num2 {
 }
 
 #[allow(
-    clippy::unnecessary_wraps,
     clippy::cast_lossless,
     clippy::cast_possible_wrap,
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss
 )]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let original = input
-        .lines()
-        .map(|l| l.parse::<i32>().unwrap())
-        .collect::<Vec<_>>();
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let original = parse_lines(input, |l| parse_at::<i32>(l, l))?;
+    mark_parsed();
     let n = original.len();
     let mut mixed = original
         .iter()
@@ -123,11 +118,12 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
     //     (mixed[1000 % n].1, mixed[2000 % n].1, mixed[3000 % n].1)
     // );
 
-    Ok((mixed[1000 % n].x + mixed[2000 % n].x + mixed[3000 % n].x).to_string())
+    Ok((mixed[1000 % n].x + mixed[2000 % n].x + mixed[3000 % n].x).into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 20,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,15 +1,16 @@
 use std::cmp;
 use std::collections::HashMap;
 
-use color_eyre::eyre::eyre;
+use color_eyre::eyre::{eyre, WrapErr};
+use log::trace;
 use nom::branch::alt;
 use nom::character::complete::alpha1;
 use nom::combinator::value;
 use nom::sequence::separated_pair;
 use nom::{bytes::complete::tag, combinator::map, sequence::tuple, IResult};
 
-use crate::daylib::Day;
-use crate::shared::parse_inum;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_all, parse_inum, ParseError};
 
 type Int = i64;
 
@@ -91,6 +92,39 @@ impl Monkey {
     }
 }
 
+// monkeys by name, every monkey they wait for has to be there and so does root
+fn parse(input: &str) -> Result<HashMap<String, Monkey>, ParseError> {
+    let lines = input
+        .lines()
+        .map(|l| {
+            parse_all(l, Monkey::parse)
+                .map(|m| (l, m))
+                .map_err(|e| e.within(input, l))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let memory = lines
+        .iter()
+        .map(|(_, m)| (m.name.clone(), m.clone()))
+        .collect::<HashMap<_, _>>();
+
+    for (l, monkey) in &lines {
+        if let Value::Calc(a, _, b) = &monkey.value {
+            if let Some(unknown) = [a, b].into_iter().find(|n| !memory.contains_key(*n)) {
+                return Err(ParseError::new(
+                    input,
+                    l,
+                    format!("unknown monkey {unknown:?}"),
+                ));
+            }
+        }
+    }
+    if !memory.contains_key("root") {
+        return Err(ParseError::new(input, input, "no root monkey"));
+    }
+
+    Ok(memory)
+}
+
 fn eval(memory: &mut HashMap<String, Monkey>) -> color_eyre::Result<()> {
     use Value::{Calc, Concrete};
 
@@ -123,55 +157,48 @@ fn eval(memory: &mut HashMap<String, Monkey>) -> color_eyre::Result<()> {
     Ok(())
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let mut memory = input
-        .lines()
-        .map(|l| Monkey::parse(l).unwrap().1)
-        .map(|m| (m.name.clone(), m))
-        .collect::<HashMap<_, _>>();
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let mut memory = parse(input)?;
+    mark_parsed();
 
-    eval(&mut memory).expect("part1 doesn't involve non-integer division");
+    eval(&mut memory).wrap_err("part 1 can't involve a non-integer division")?;
 
     if let Value::Concrete(x) = memory["root"].value {
-        Ok(x.to_string())
+        Ok(x.into())
     } else {
-        panic!("unexpected root monkey value {:?}", memory["root"])
+        Err(eyre!("root monkey didn't get a value: {:?}", memory["root"]))
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    use Value::{Calc, Concrete};
+// part 2 closes in on humn's number by guessing, which doesn't always get there
+const MAX_GUESSES: usize = 1_000;
 
-    let mut memory = input
-        .lines()
-        .map(|l| Monkey::parse(l).unwrap().1)
-        .map(|m| (m.name.clone(), m))
-        .collect::<HashMap<_, _>>();
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    use Value::{Calc, Concrete};
 
-    if let Monkey {
-        name,
-        value: Calc(a, _, b),
-    } = &memory["root"]
-    {
-        memory.insert(
-            "root".to_string(),
-            Monkey {
-                name: name.clone(),
-                value: Calc(a.clone(), Op::Eq, b.clone()),
-            },
-        );
-    } else {
-        unreachable!();
-    }
+    let mut memory = parse(input)?;
+    mark_parsed();
+
+    let Calc(a, _, b) = memory["root"].value.clone() else {
+        return Err(eyre!(
+            "root monkey has to wait for two monkeys, got {:?}",
+            memory["root"]
+        ));
+    };
+    memory.insert(
+        "root".to_string(),
+        Monkey {
+            name: "root".to_string(),
+            value: Calc(a, Op::Eq, b),
+        },
+    );
 
     let mut guess: Int = 0;
     let mut step: Int = 1;
     let mut last_distance: Option<Int> = None;
     let mut found = false;
-    let result: Int;
-    loop {
-        //println!("guess: {guess}");
+    for guesses in 0..MAX_GUESSES {
+        trace!("guess {guesses}: {guess}");
         let mut memory = memory.clone();
 
         memory.insert(
@@ -189,19 +216,17 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
         }
 
         if step == 0 {
-            result = 42;
-            break;
+            return Err(eyre!("the search for humn got stuck at {guess}"));
         }
 
         if let Concrete(guess_result) = memory["root"].value {
-            println!(
+            trace!(
                 "new distance: {}, last distance: {}",
                 guess_result.abs(),
                 last_distance.unwrap_or(0)
             );
             if guess_result == 0 {
-                result = guess;
-                break;
+                return Ok(guess.into());
             }
 
             let new_distance = guess_result.abs();
@@ -228,15 +253,16 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
             guess += step;
             last_distance = Some(new_distance);
         } else {
-            unreachable!()
+            return Err(eyre!("root monkey didn't get a value: {:?}", memory["root"]));
         }
     }
 
-    Ok(result.to_string())
+    Err(eyre!("didn't find humn's number in {MAX_GUESSES} guesses"))
 }
 
 pub(crate) const DAY: Day = Day {
     number: 21,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
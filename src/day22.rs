@@ -1,41 +1,13 @@
 use std::collections::HashMap;
-use std::iter;
-use std::ops::{Index, IndexMut};
 
+use log::trace;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::digit1;
 use nom::combinator::{map, value};
-use nom::error::Error as NomError;
 use nom::multi::many1;
-use nom::Finish;
 
-use crate::daylib::Day;
-
-#[derive(Debug)]
-struct Grid<T> {
-    d: Vec<T>,
-    w: usize,
-    h: usize,
-}
-
-impl<T> Index<(usize, usize)> for Grid<T> {
-    type Output = T;
-
-    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        self.d
-            .get(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
-    }
-}
-
-impl<T> IndexMut<(usize, usize)> for Grid<T> {
-    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        self.d
-            .get_mut(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
-    }
-}
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_all, parse_unum, Grid, ParseError};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Tile {
@@ -44,69 +16,54 @@ enum Tile {
     Oob,
 }
 
-impl Grid<Tile> {
-    fn parse(s: &str) -> Self {
-        use Tile::{Oob, Open, Wall};
-
-        let w = s.lines().map(str::len).max().unwrap();
-        let h = s.lines().count();
-        let d = s
-            .lines()
-            .flat_map(|l| {
-                l.chars()
-                    .map(|c| match c {
-                        ' ' => Oob,
-                        '.' => Open,
-                        '#' => Wall,
-                        _ => panic!("unexpected char {c}"),
-                    })
-                    .chain(iter::repeat(Oob))
-                    .take(w)
-            })
-            .collect::<Vec<_>>();
-        assert_eq!(d.len(), w * h);
+fn parse_map(s: &str) -> Result<Grid<Tile>, ParseError> {
+    use Tile::{Oob, Open, Wall};
 
-        Self { d, w, h }
-    }
+    Grid::parse_padded(s, Oob, |c| match c {
+        ' ' => Some(Oob),
+        '.' => Some(Open),
+        '#' => Some(Wall),
+        _ => None,
+    })
+}
 
-    #[allow(dead_code)]
-    fn print(&self, path: Option<&Vec<Position>>) {
-        use Tile::{Oob, Open, Wall};
-
-        let mut char_grid = Grid::<char> {
-            d: self
-                .d
-                .iter()
-                .map(|t| match t {
-                    Open => '.',
-                    Wall => '#',
-                    Oob => ' ',
-                })
-                .collect(),
-            w: self.w,
-            h: self.h,
-        };
+fn format_map(map: &Grid<Tile>, path: Option<&Vec<Position>>) -> String {
+    use Tile::{Oob, Open, Wall};
 
-        if let Some(path) = path {
-            use Direction::{D, L, R, U};
+    let mut char_grid = map.map(|t| match t {
+        Open => '.',
+        Wall => '#',
+        Oob => ' ',
+    });
 
-            for p in path {
-                char_grid[(p.x, p.y)] = match p.d {
-                    U => '^',
-                    R => '>',
-                    D => 'v',
-                    L => '<',
-                }
-            }
+    if let Some(path) = path {
+        use Direction::{D, L, R, U};
 
-            let last = path.last().unwrap();
-            char_grid[(last.x, last.y)] = '*';
+        for p in path {
+            char_grid[(p.x, p.y)] = match p.d {
+                U => '^',
+                R => '>',
+                D => 'v',
+                L => '<',
+            }
         }
 
-        for chunk in char_grid.d.chunks(self.w) {
-            println!("{}", chunk.iter().collect::<String>());
-        }
+        let last = path.last().unwrap();
+        char_grid[(last.x, last.y)] = '*';
     }
+
+    char_grid.to_string()
+}
+
+// first and last tile of a row or column that's on the map
+fn edges<'a>(line: impl Iterator<Item = &'a Tile>) -> (usize, usize) {
+    let mut on_map = line
+        .enumerate()
+        .skip_while(|(_, t)| **t == Tile::Oob)
+        .take_while(|(_, t)| **t != Tile::Oob)
+        .map(|(i, _)| i);
+    let a = on_map.next().expect("every line should have some map on it");
+    (a, on_map.last().unwrap_or(a))
 }
 
 // moves to wherever we're supposed to be if we're in Position and make one step forward
@@ -117,40 +74,14 @@ fn calc_teleports_part1(map: &Grid<Tile>) -> TeleportMap {
 
     let mut t_map = TeleportMap::new();
 
-    for y in 0..map.h {
-        let mut a = None;
-        let mut b = None;
-        for x in 0..map.w {
-            if a.is_none() {
-                if map[(x, y)] != Tile::Oob {
-                    a = Some(x);
-                }
-            } else if map[(x, y)] == Tile::Oob {
-                b = Some(x - 1);
-                break;
-            }
-        }
-        let a = a.unwrap();
-        let b = b.unwrap_or(map.w - 1);
+    for (y, row) in map.rows().enumerate() {
+        let (a, b) = edges(row.iter());
         t_map.insert(Position::new(a, y, L), Position::new(b, y, L));
         t_map.insert(Position::new(b, y, R), Position::new(a, y, R));
     }
 
-    for x in 0..map.w {
-        let mut a = None;
-        let mut b = None;
-        for y in 0..map.h {
-            if a.is_none() {
-                if map[(x, y)] != Tile::Oob {
-                    a = Some(y);
-                }
-            } else if map[(x, y)] == Tile::Oob {
-                b = Some(y - 1);
-                break;
-            }
-        }
-        let a = a.unwrap();
-        let b = b.unwrap_or(map.h - 1);
+    for x in 0..map.width() {
+        let (a, b) = edges(map.column(x));
         t_map.insert(Position::new(x, a, U), Position::new(x, b, U));
         t_map.insert(Position::new(x, b, D), Position::new(x, a, D));
     }
@@ -167,7 +98,7 @@ fn calc_teleports_part2(map: &Grid<Tile>) -> TeleportMap {
         face: usize,
         side: Direction,
     ) -> Vec<(usize, usize)> {
-        let face_size = map.w / 3;
+        let face_size = map.width() / 3;
         // top left corner
         let face_x = faces[face].0 * face_size;
         let face_y = faces[face].1 * face_size;
@@ -216,7 +147,7 @@ fn calc_teleports_part2(map: &Grid<Tile>) -> TeleportMap {
             }
             face1_coords
                 .into_iter()
-                .zip(face2_coords.into_iter())
+                .zip(face2_coords)
                 //.inspect(|x| println!("{x:?} f1 {} d1 {:?} f2 {} d2 {:?}", *f1, *d1, *f2, *d2,))
                 .map(|((x1, y1), (x2, y2))| {
                     (Position::new(x1, y1, *d1), Position::new(x2, y2, *d2))
@@ -232,17 +163,32 @@ enum PathPart {
     R,
 }
 
-fn parse_path(s: &str) -> Vec<PathPart> {
+fn parse_path(s: &str) -> Result<Vec<PathPart>, ParseError> {
     use PathPart::{Move, L, R};
 
-    many1(alt((
-        value(L, tag::<_, _, NomError<_>>("L")),
-        value(R, tag("R")),
-        map(digit1, |s: &str| Move(s.parse().unwrap())),
-    )))(s)
-    .finish()
-    .unwrap()
-    .1
+    parse_all(
+        s,
+        many1(alt((
+            value(L, tag("L")),
+            value(R, tag("R")),
+            map(parse_unum, Move),
+        ))),
+    )
+}
+
+fn parse(input: &str) -> Result<(Grid<Tile>, Vec<PathPart>), ParseError> {
+    let Some((map_s, path_s)) = input.split_once("\n\n") else {
+        return Err(ParseError::new(
+            input,
+            input,
+            "expected the map and the path after an empty line",
+        ));
+    };
+
+    Ok((
+        parse_map(map_s).map_err(|e| e.within(input, map_s))?,
+        parse_path(path_s).map_err(|e| e.within(input, path_s))?,
+    ))
 }
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
@@ -291,13 +237,10 @@ impl Position {
 
     fn fresh(map: &Grid<Tile>) -> Self {
         let x = map
-            .d
+            .row(0)
             .iter()
-            .enumerate()
-            .take(map.w)
-            .find(|(_, x)| Tile::Open.eq(x))
-            .expect("should have one open tile in the first row")
-            .0;
+            .position(|x| *x == Tile::Open)
+            .expect("should have one open tile in the first row");
         Self {
             x,
             y: 0,
@@ -347,11 +290,9 @@ impl Position {
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    let mut it = input.split("\n\n");
-    let map = Grid::parse(it.next().expect("map should be there"));
-    let path = parse_path(it.next().expect("path should be there"));
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let (map, path) = parse(input)?;
+    mark_parsed();
     let teleport_map = calc_teleports_part1(&map);
 
     let positions = path
@@ -362,7 +303,7 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
         })
         .collect::<Vec<_>>();
 
-    //map.print(Some(&positions));
+    //trace!("final path:\n{}", format_map(&map, Some(&positions)));
 
     let last = positions.last().unwrap();
     {
@@ -376,15 +317,13 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
                 L => 2,
                 U => 3,
             })
-        .to_string())
+        .try_into()?)
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
-    let mut it = input.split("\n\n");
-    let map = Grid::parse(it.next().expect("map should be there"));
-    let path = parse_path(it.next().expect("path should be there"));
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
+    let (map, path) = parse(input)?;
+    mark_parsed();
     let teleport_map = calc_teleports_part2(&map);
 
     // println!(
@@ -404,7 +343,7 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
         })
         .collect::<Vec<_>>();
 
-    map.print(Some(&positions));
+    trace!("final path:\n{}", format_map(&map, Some(&positions)));
 
     let last = positions.last().unwrap();
     {
@@ -418,7 +357,7 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
                 L => 2,
                 U => 3,
             })
-        .to_string())
+        .try_into()?)
     }
 }
 
@@ -426,4 +365,5 @@ pub(crate) const DAY: Day = Day {
     number: 22,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,12 +1,10 @@
 use std::collections::{HashMap, VecDeque};
-use std::iter;
 
 use itertools::Itertools;
+use log::trace;
 
-use crate::daylib::Day;
-
-const GRID_WIDTH: isize = 1000;
-const GRID_HEIGHT: isize = 1000;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{ParseError, SparseGrid, DELTAS8};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Loc {
@@ -16,52 +14,55 @@ enum Loc {
 
 #[derive(Debug)]
 struct Grid {
-    d: Vec<Loc>,
+    d: SparseGrid<(isize, isize), Loc>,
     elves: Vec<(isize, isize)>,
-    min_seen_x: isize,
-    min_seen_y: isize,
-    max_seen_x: isize,
-    max_seen_y: isize,
 }
 
 impl Grid {
-    fn parse(s: &str) -> Self {
+    fn parse(s: &str) -> Result<Self, ParseError> {
         let mut g = Grid {
-            d: vec![Loc::Ground; (GRID_HEIGHT * GRID_WIDTH) as usize],
+            d: SparseGrid::new(Loc::Ground),
             elves: vec![],
-            min_seen_x: 0,
-            min_seen_y: 0,
-            max_seen_x: 0,
-            max_seen_y: 0,
         };
-        s.lines().enumerate().for_each(|(y, line)| {
-            line.chars().enumerate().for_each(|(x, c)| {
-                if c == '#' {
-                    let x = isize::try_from(x).unwrap();
-                    let y = isize::try_from(y).unwrap();
-                    g.elves.push((x, y));
-                    *g.get_mut((x, y)) = Loc::Elf;
+        for (y, line) in s.lines().enumerate() {
+            for (x, (i, c)) in line.char_indices().enumerate() {
+                match c {
+                    '#' => {
+                        let x = isize::try_from(x).unwrap();
+                        let y = isize::try_from(y).unwrap();
+                        g.elves.push((x, y));
+                        g.d[(x, y)] = Loc::Elf;
+                    }
+                    '.' => {}
+                    other => {
+                        return Err(ParseError::new(
+                            s,
+                            &line[i..],
+                            format!("unexpected {other:?}, expected an elf (#) or ground (.)"),
+                        ))
+                    }
                 }
-            });
-        });
+            }
+        }
+        if g.elves.is_empty() {
+            return Err(ParseError::new(s, s, "no elves"));
+        }
 
-        g
+        Ok(g)
     }
 
-    fn print(&self) {
+    fn format(&self) -> String {
         let (bmin, bmax) = self.bounding_box();
-        for y in bmin.1..=bmax.1 {
-            for x in bmin.0..=bmax.0 {
-                print!(
-                    "{}",
-                    match self.get((x, y)) {
+        (bmin.1..=bmax.1)
+            .map(|y| {
+                (bmin.0..=bmax.0)
+                    .map(|x| match self.d[(x, y)] {
                         Loc::Elf => '#',
                         Loc::Ground => '.',
-                    }
-                );
-            }
-            println!();
-        }
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
     }
 
     fn bounding_box(&self) -> ((isize, isize), (isize, isize)) {
@@ -73,47 +74,22 @@ impl Grid {
         )
     }
 
-    // can't use Index/IndexMut with negative coords
-    fn get(&self, (x, y): (isize, isize)) -> Loc {
-        let i = (y + GRID_HEIGHT / 2) * GRID_WIDTH + (x + GRID_WIDTH / 2);
-        let i = usize::try_from(i).unwrap();
-        *(self
-            .d
-            .get(i)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}")))
-    }
-
-    fn get_mut(&mut self, (x, y): (isize, isize)) -> &mut Loc {
-        self.min_seen_x = self.min_seen_x.min(x);
-        self.min_seen_y = self.min_seen_y.min(y);
-        self.max_seen_x = self.max_seen_x.max(x);
-        self.max_seen_y = self.max_seen_y.max(y);
-
-        let i = (y + GRID_HEIGHT / 2) * GRID_WIDTH + (x + GRID_WIDTH / 2);
-        let i = usize::try_from(i).unwrap();
-
-        self.d
-            .get_mut(i)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
-    }
-
     fn move_elf(&mut self, i: usize, d: Direction) {
         let from @ (x, y) = self.elves[i];
         let (dx, dy) = d.delta();
         let to = (x + dx, y + dy);
 
-        assert_eq!(self.get(to), Loc::Ground);
+        assert_eq!(self.d[to], Loc::Ground);
         self.elves[i] = to;
-        // can't use mem::swap b/c it won't update our bounding box
-        *self.get_mut(from) = Loc::Ground;
-        *self.get_mut(to) = Loc::Elf;
+        self.d[from] = Loc::Ground;
+        self.d[to] = Loc::Elf;
     }
 
     fn check(&self, i: usize, d: Direction) -> Option<(isize, isize)> {
         let (x, y) = self.elves[i];
         if d.check_deltas()
             .iter()
-            .all(|(dx, dy)| self.get((x + dx, y + dy)) == Loc::Ground)
+            .all(|(dx, dy)| self.d[(x + dx, y + dy)] == Loc::Ground)
         {
             let (dx, dy) = d.delta();
             Some((x + dx, y + dy))
@@ -124,9 +100,9 @@ impl Grid {
 
     fn free(&self, i: usize) -> bool {
         let (x, y) = self.elves[i];
-        (-1..=1)
-            .flat_map(|x| iter::repeat(x).zip(-1..=1))
-            .all(|(dx, dy)| (dx == 0 && dy == 0) || self.get((x + dx, y + dy)) == Loc::Ground)
+        DELTAS8
+            .iter()
+            .all(|(dx, dy)| self.d[(x + dx, y + dy)] == Loc::Ground)
     }
 }
 
@@ -162,12 +138,12 @@ impl Direction {
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
     use Direction::{E, N, S, W};
 
-    let mut grid = Grid::parse(input);
-    grid.print();
+    let mut grid = Grid::parse(input)?;
+    mark_parsed();
+    trace!("initial grid:\n{}", grid.format());
 
     let mut directions = VecDeque::from([N, S, W, E]);
     for _round in 0..10 {
@@ -195,15 +171,15 @@ fn solve1(input: &str) -> color_eyre::Result<String> {
 
     let (bmin, bmax) = grid.bounding_box();
     let n_ground = (bmax.0.abs_diff(bmin.0) + 1) * (bmax.1.abs_diff(bmin.1) + 1) - grid.elves.len();
-    Ok(n_ground.to_string())
+    Ok(n_ground.try_into()?)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
     use Direction::{E, N, S, W};
 
-    let mut grid = Grid::parse(input);
-    grid.print();
+    let mut grid = Grid::parse(input)?;
+    mark_parsed();
+    trace!("initial grid:\n{}", grid.format());
 
     let mut directions = VecDeque::from([N, S, W, E]);
     let mut final_round = None;
@@ -236,11 +212,12 @@ fn solve2(input: &str) -> color_eyre::Result<String> {
         directions.rotate_left(1);
     }
 
-    Ok((final_round.unwrap() + 1).to_string())
+    Ok((final_round.unwrap() + 1).into())
 }
 
 pub(crate) const DAY: Day = Day {
     number: 23,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,12 +1,14 @@
-use std::collections::{HashSet, VecDeque};
-use std::iter;
+use std::cell::RefCell;
 use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
 use color_eyre::eyre::eyre;
 use itertools::Itertools;
+use log::trace;
 
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::search::{bfs, Search};
+use crate::shared::{self, ParseError};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Direction {
@@ -93,9 +95,7 @@ impl TryFrom<char> for Loc {
 
 #[derive(Debug, Clone)]
 struct Grid {
-    d: Vec<Loc>,
-    w: usize,
-    h: usize,
+    d: shared::Grid<Loc>,
     blizzards: Vec<(usize, usize, Direction)>,
     start: (usize, usize),
     end: (usize, usize),
@@ -103,49 +103,38 @@ struct Grid {
 
 impl Grid {
     #[allow(dead_code)]
-    fn to_char(&self) -> CharGrid {
-        CharGrid {
-            d: self.d.iter().copied().map(char::from).collect(),
-            w: self.w,
-        }
+    fn print(&self) {
+        trace!("{}", self.d.map(|l| char::from(*l)));
     }
 }
 
 impl FromStr for Grid {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
-    #[allow(clippy::match_on_vec_items)]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let w = s
-            .lines()
-            .next()
-            .ok_or(eyre!("at least one line should be present"))?
-            .len();
-        let h = s.lines().count();
-        let d = s
-            .lines()
-            .flat_map(|l| l.chars().map(Loc::try_from))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let blizzards = (1..h)
-            .flat_map(|y| (1..w).zip(iter::repeat(y)))
-            .filter_map(|(x, y)| match d[y * w + x] {
-                Loc::Blizzard(d) => Some((x, y, d)),
+        let (Some(first_line), Some(last_line)) = (s.lines().next(), s.lines().last()) else {
+            return Err(ParseError::new(s, s, "at least one line should be present"));
+        };
+        let d = shared::Grid::parse(s, |c| Loc::try_from(c).ok())?;
+        let h = d.height();
+
+        let blizzards = d
+            .cells()
+            .filter_map(|((x, y), l)| match l {
+                Loc::Blizzard(d) => Some((x, y, *d)),
                 _ => None,
             })
             .collect();
 
-        let start_x = (0..w)
-            .find(|i| d[*i] == Loc::Ground)
-            .expect("must have entry in the first row");
-        let end_x = (0..w)
-            .find(|i| d[(w * (h - 1)) + *i] == Loc::Ground)
-            .expect("must have exit in the last row");
+        let Some(start_x) = d.row(0).iter().position(|l| *l == Loc::Ground) else {
+            return Err(ParseError::new(s, first_line, "must have entry in the first row"));
+        };
+        let Some(end_x) = d.row(h - 1).iter().position(|l| *l == Loc::Ground) else {
+            return Err(ParseError::new(s, last_line, "must have exit in the last row"));
+        };
 
         Ok(Grid {
             d,
-            w,
-            h,
             blizzards,
             start: (start_x, 0),
             end: (end_x, h - 1),
@@ -156,60 +145,14 @@ impl FromStr for Grid {
 impl Index<(usize, usize)> for Grid {
     type Output = Loc;
 
-    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        self.d
-            .get(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        &self.d[pos]
     }
 }
 
 impl IndexMut<(usize, usize)> for Grid {
-    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        self.d
-            .get_mut(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
-    }
-}
-
-#[derive(Debug, Clone)]
-struct CharGrid {
-    d: Vec<char>,
-    w: usize,
-}
-
-impl Index<(usize, usize)> for CharGrid {
-    type Output = char;
-
-    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        self.d
-            .get(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
-    }
-}
-
-impl IndexMut<(usize, usize)> for CharGrid {
-    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        self.d
-            .get_mut(y * self.w + x)
-            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
-    }
-}
-
-impl CharGrid {
-    #[allow(dead_code)]
-    fn print(&self) {
-        println!(
-            "{}",
-            itertools::intersperse(
-                self.d
-                    .iter()
-                    .chunks(self.w)
-                    .into_iter()
-                    .map(Iterator::collect),
-                "\n".to_string()
-            )
-            .collect::<String>()
-        );
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        &mut self.d[pos]
     }
 }
 
@@ -253,12 +196,12 @@ impl GridCache {
                     y.checked_add_signed(dy).unwrap(),
                 );
                 if tx == 0 {
-                    tx = prev_grid.w - 2;
-                } else if tx == prev_grid.w - 1 {
+                    tx = prev_grid.d.width() - 2;
+                } else if tx == prev_grid.d.width() - 1 {
                     tx = 1;
                 } else if ty == 0 {
-                    ty = prev_grid.h - 2;
-                } else if ty == prev_grid.h - 1 {
+                    ty = prev_grid.d.height() - 2;
+                } else if ty == prev_grid.d.height() - 1 {
                     ty = 1;
                 }
                 (tx, ty, *d)
@@ -284,7 +227,7 @@ impl GridCache {
 
     fn on(&mut self, minute: usize) -> &Grid {
         let grid = self.grids.first().unwrap();
-        let minute = minute % lcm(grid.w, grid.h);
+        let minute = minute % lcm(grid.d.width(), grid.d.height());
         if minute < self.grids.len() {
             &self.grids[minute]
         } else {
@@ -296,39 +239,19 @@ impl GridCache {
     }
 }
 
-fn search(
-    grid_cache: &mut GridCache,
+// a minute per move, while the blizzards move too
+struct Expedition<'a> {
+    grid_cache: RefCell<&'a mut GridCache>,
     start: (usize, usize),
     end: (usize, usize),
-    start_time: usize,
-) -> (usize, Vec<Option<Direction>>) {
-    #[derive(Debug)]
-    struct Node {
-        pos: (usize, usize),
-        t: usize,
-        path: Vec<Option<Direction>>,
-    }
-
-    let mut frontier = VecDeque::from([Node {
-        pos: start,
-        t: start_time,
-        path: vec![],
-    }]);
-    let mut visited = HashSet::new();
-
-    let mut result = None;
-    while let Some(Node { pos, t, path }) = frontier.pop_front() {
-        if visited.contains(&(pos, t)) {
-            continue;
-        }
-
-        visited.insert((pos, t));
+}
 
-        if pos == end {
-            result = Some((t, path));
-            break;
-        }
+impl Search for Expedition<'_> {
+    // where and when
+    type State = ((usize, usize), usize);
 
+    fn successors(&self, &(pos, t): &Self::State) -> Vec<(Self::State, u32)> {
+        let mut grid_cache = self.grid_cache.borrow_mut();
         let next_grid = grid_cache.on(t + 1);
 
         [
@@ -347,49 +270,68 @@ fn search(
                     (pos.0.checked_add_signed(dx)?, pos.1.checked_add_signed(dy)?)
                 }
             };
-            if next_pos.1 > start.1.max(end.1) {
+            if next_pos.1 > self.start.1.max(self.end.1) {
                 // this removes the out-of-bounds access when we start at the end
                 // and consider going down
                 None
             } else if next_grid[next_pos] == Loc::Ground {
-                Some((m, next_pos))
+                Some(((next_pos, t + 1), 1))
             } else {
                 None
             }
         })
-        .map(|(m, next_pos)| Node {
-            pos: next_pos,
-            t: t + 1,
-            path: path.iter().copied().chain([*m]).collect(),
-        })
-        .for_each(|node| frontier.push_back(node));
+        .collect()
     }
 
-    result.expect("the loop above should find a path")
+    fn is_goal(&self, (pos, _): &Self::State) -> bool {
+        *pos == self.end
+    }
+}
+
+// the minute we get to `end`
+fn search(
+    grid_cache: &mut GridCache,
+    start: (usize, usize),
+    end: (usize, usize),
+    start_time: usize,
+) -> usize {
+    let expedition = Expedition {
+        grid_cache: RefCell::new(grid_cache),
+        start,
+        end,
+    };
+
+    bfs(&expedition, (start, start_time))
+        .expect("there's always a way around the blizzards")
+        .goal()
+        .1
 }
 
-fn solve1(input: &str) -> color_eyre::Result<String> {
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
     let grid = input.parse::<Grid>()?;
+    mark_parsed();
     let mut cache = GridCache::new(grid.clone());
 
-    let (duration, _steps) = search(&mut cache, grid.start, grid.end, 0);
+    let duration = search(&mut cache, grid.start, grid.end, 0);
 
-    Ok(duration.to_string())
+    Ok(duration.try_into()?)
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(input: &str) -> color_eyre::Result<Answer> {
     let grid = input.parse::<Grid>()?;
+    mark_parsed();
     let mut cache = GridCache::new(grid.clone());
 
-    let (duration1, _steps) = search(&mut cache, grid.start, grid.end, 0);
-    let (duration2, _steps) = search(&mut cache, grid.end, grid.start, duration1);
-    let (duration3, _steps) = search(&mut cache, grid.start, grid.end, duration2);
+    let duration1 = search(&mut cache, grid.start, grid.end, 0);
+    let duration2 = search(&mut cache, grid.end, grid.start, duration1);
+    let duration3 = search(&mut cache, grid.start, grid.end, duration2);
 
-    Ok(duration3.to_string())
+    Ok(duration3.try_into()?)
 }
 
 pub(crate) const DAY: Day = Day {
     number: 24,
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
@@ -1,13 +1,14 @@
-use crate::daylib::Day;
+use crate::daylib::{mark_parsed, Answer, Day};
+use crate::shared::{parse_lines, ParseError};
 
-fn snafu_dig_to_dec(c: char) -> isize {
+fn snafu_dig_to_dec(c: char) -> Option<isize> {
     match c {
-        '2' => 2,
-        '1' => 1,
-        '0' => 0,
-        '-' => -1,
-        '=' => -2,
-        _ => panic!("unknown snafu digit {c}"),
+        '2' => Some(2),
+        '1' => Some(1),
+        '0' => Some(0),
+        '-' => Some(-1),
+        '=' => Some(-2),
+        _ => None,
     }
 }
 
@@ -22,13 +23,15 @@ fn dec_dig_to_snafu(x: isize) -> char {
     }
 }
 
-fn snafu_to_dec(x: &str) -> isize {
-    x.chars()
-        .map(snafu_dig_to_dec)
+fn snafu_to_dec(x: &str) -> Result<isize, ParseError> {
+    x.char_indices()
         .rev()
         .enumerate()
-        .fold(0, |acc, (i, x)| {
-            acc + x * 5isize.pow(u32::try_from(i).unwrap())
+        .try_fold(0, |acc, (i, (pos, c))| {
+            let digit = snafu_dig_to_dec(c).ok_or_else(|| {
+                ParseError::new(x, &x[pos..], format!("unknown snafu digit {c:?}"))
+            })?;
+            Ok(acc + digit * 5isize.pow(u32::try_from(i).unwrap()))
         })
 }
 
@@ -48,6 +51,28 @@ fn dec_to_snafu(mut x: isize) -> String {
     s.chars().rev().collect()
 }
 
+fn solve1(input: &str) -> color_eyre::Result<Answer> {
+    let numbers = parse_lines(input, snafu_to_dec)?;
+    mark_parsed();
+
+    Ok(dec_to_snafu(numbers.into_iter().sum()).into())
+}
+
+#[allow(clippy::unnecessary_wraps)]
+fn solve2(_input: &str) -> color_eyre::Result<Answer> {
+    // nothing to parse
+    mark_parsed();
+
+    Ok("Celebration!".into())
+}
+
+pub(crate) const DAY: Day = Day {
+    number: 25,
+    part1: solve1,
+    part2: solve2,
+    renders: &[],
+};
+
 #[cfg(test)]
 mod tests {
     use test_case::test_case;
@@ -65,7 +90,7 @@ mod tests {
     #[test_case("1-0---0" => 12345)]
     #[test_case("1121-1110-1=0" => 314_159_265)]
     fn snafu_to_dec_tests(x: &str) -> isize {
-        snafu_to_dec(x)
+        snafu_to_dec(x).unwrap()
     }
 
     #[test_case(1 => "1")]
@@ -82,19 +107,3 @@ mod tests {
         dec_to_snafu(x)
     }
 }
-
-#[allow(clippy::unnecessary_wraps)]
-fn solve1(input: &str) -> color_eyre::Result<String> {
-    Ok(dec_to_snafu(input.lines().map(snafu_to_dec).sum()))
-}
-
-#[allow(clippy::unnecessary_wraps)]
-fn solve2(_input: &str) -> color_eyre::Result<String> {
-    Ok("Celebration!".to_string())
-}
-
-pub(crate) const DAY: Day = Day {
-    number: 25,
-    part1: solve1,
-    part2: solve2,
-};
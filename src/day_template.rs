@@ -1,8 +1,16 @@
-fn solve1(input: &str) -> color_eyre::Result<String> {
+use crate::daylib::{mark_parsed, Answer, Day};
+
+fn solve1(_input: &str) -> color_eyre::Result<Answer> {
+    // parse the input first
+    mark_parsed();
+
     todo!()
 }
 
-fn solve2(input: &str) -> color_eyre::Result<String> {
+fn solve2(_input: &str) -> color_eyre::Result<Answer> {
+    // parse the input first
+    mark_parsed();
+
     todo!()
 }
 
@@ -10,4 +18,5 @@ pub(crate) const DAY: Day = Day {
     number: todo!(),
     part1: solve1,
     part2: solve2,
+    renders: &[],
 };
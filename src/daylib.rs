@@ -1,5 +1,180 @@
-pub(crate) struct Day {
-    pub(crate) number: u8,
-    pub(crate) part1: fn(&str) -> color_eyre::Result<String>,
-    pub(crate) part2: fn(&str) -> color_eyre::Result<String>,
+use std::cell::Cell;
+use std::fmt;
+use std::num::TryFromIntError;
+use std::str::FromStr;
+use std::time::Instant;
+
+use color_eyre::eyre::eyre;
+
+use crate::shared::Image;
+
+// what a solver produces, `Display` gives the text that goes into answer files
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Answer {
+    Int(i64),
+    Text(String),
+    // ASCII-art screens like day 10's, one string per row
+    Grid(Vec<String>),
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(i64::from(n))
+                }
+            }
+        )*
+    };
+}
+
+// the ones that don't always fit into i64, solvers use `Answer::try_from(n)?` for these
+macro_rules! answer_try_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<$t> for Answer {
+                type Error = TryFromIntError;
+
+                fn try_from(n: $t) -> Result<Self, Self::Error> {
+                    Ok(Answer::Int(i64::try_from(n)?))
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, i64, u16, u32);
+answer_try_from_int!(isize, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+pub type Solver = fn(&str) -> color_eyre::Result<Answer>;
+
+// what a render produces, images get written in whatever format the output path asks for
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Rendered {
+    Text(String),
+    Image(Image),
+}
+
+// another way to look at a day's input than its answers, like the path it found
+pub struct Render {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub render: fn(&str) -> color_eyre::Result<Rendered>,
+}
+
+pub struct Day {
+    pub number: u8,
+    pub part1: Solver,
+    pub part2: Solver,
+    pub renders: &'static [Render],
+}
+
+impl Day {
+    pub fn solver(&self, part: DayPart) -> Solver {
+        match part {
+            DayPart::First => self.part1,
+            DayPart::Second => self.part2,
+        }
+    }
+
+    pub fn render(&self, name: &str) -> color_eyre::Result<&Render> {
+        self.renders.iter().find(|r| r.name == name).ok_or_else(|| {
+            let known = self
+                .renders
+                .iter()
+                .map(|r| format!("\n  {}: {}", r.name, r.help))
+                .collect::<String>();
+            match known.as_str() {
+                "" => eyre!("day {} has no renders", self.number),
+                known => eyre!(
+                    "day {} has no {name:?} render, pick one of:{known}",
+                    self.number
+                ),
+            }
+        })
+    }
+}
+
+pub struct Year {
+    pub number: u16,
+    pub days: &'static [Day],
+}
+
+impl Year {
+    pub fn day(&self, number: u8) -> color_eyre::Result<&Day> {
+        self.days
+            .iter()
+            .find(|d| d.number == number)
+            .ok_or_else(|| eyre!("no solver for {} day {number}", self.number))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum DayPart {
+    First,
+    Second,
+}
+
+impl DayPart {
+    pub const BOTH: [DayPart; 2] = [DayPart::First, DayPart::Second];
+}
+
+impl FromStr for DayPart {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> color_eyre::Result<Self> {
+        use DayPart::{First, Second};
+
+        match s {
+            "1" => Ok(First),
+            "2" => Ok(Second),
+            other => Err(eyre!("expected 1 or 2, got {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for DayPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayPart::First => write!(f, "1"),
+            DayPart::Second => write!(f, "2"),
+        }
+    }
+}
+
+thread_local! {
+    static PARSED_AT: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+// solvers call this once the input is parsed so `bench` can time parsing and solving separately
+pub(crate) fn mark_parsed() {
+    PARSED_AT.with(|p| p.set(Some(Instant::now())));
+}
+
+// when the last solver on this thread called mark_parsed, if it did
+pub fn take_parsed_at() -> Option<Instant> {
+    PARSED_AT.with(Cell::take)
 }
@@ -0,0 +1,260 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre::{eyre, WrapErr};
+use log::debug;
+
+use crate::runner;
+
+pub(crate) const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+const SESSION_ENV: &str = "AOC_SESSION";
+// looked up in order when AOC_SESSION isn't set, the home one is relative to $HOME
+const SESSION_FILE: &str = ".aoc_session";
+const HOME_SESSION_FILE: &str = ".config/aoc/session";
+
+// when the last request went out, shared between runs so back-to-back fetches still wait
+const STAMP_FILE: &str = ".last_fetch";
+
+#[derive(Debug, Clone)]
+pub(crate) struct FetchOpts {
+    pub(crate) base_url: String,
+    // minimal time between two requests to the server
+    pub(crate) delay: Duration,
+    pub(crate) force: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum Fetched {
+    Cached,
+    Downloaded,
+}
+
+// the session cookie value, from AOC_SESSION or the first session file that exists
+pub(crate) fn session_token() -> color_eyre::Result<String> {
+    if let Ok(token) = env::var(SESSION_ENV) {
+        return Ok(token.trim().to_string());
+    }
+
+    let mut candidates = vec![PathBuf::from(SESSION_FILE)];
+    if let Some(home) = env::var_os("HOME") {
+        candidates.push(Path::new(&home).join(HOME_SESSION_FILE));
+    }
+
+    for path in &candidates {
+        match fs::read_to_string(path) {
+            Ok(token) => return Ok(token.trim().to_string()),
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(e)
+                    .wrap_err_with(|| format!("can't read session from {}", path.display()))
+            }
+        }
+    }
+
+    Err(eyre!(
+        "no session token, set {SESSION_ENV} or put it into {SESSION_FILE} or ~/{HOME_SESSION_FILE}"
+    ))
+}
+
+pub(crate) fn input_path(data_dir: &Path, year: u16, day: u8) -> PathBuf {
+    runner::day_dir(data_dir, year, day).join("input.txt")
+}
+
+pub(crate) struct Fetcher {
+    agent: ureq::Agent,
+    session: String,
+    opts: FetchOpts,
+    data_dir: PathBuf,
+}
+
+impl Fetcher {
+    pub(crate) fn new(session: String, opts: FetchOpts, data_dir: impl Into<PathBuf>) -> Self {
+        Fetcher {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .user_agent("adventofcode2022 input fetcher")
+                .build(),
+            session,
+            opts,
+            data_dir: data_dir.into(),
+        }
+    }
+
+    // stores the input in the day's data dir unless it's already there
+    pub(crate) fn fetch(&self, year: u16, day: u8) -> color_eyre::Result<Fetched> {
+        let path = input_path(&self.data_dir, year, day);
+        if !self.opts.force && path.exists() {
+            return Ok(Fetched::Cached);
+        }
+
+        self.wait_for_rate_limit()?;
+
+        let url = format!(
+            "{}/{year}/day/{day}/input",
+            self.opts.base_url.trim_end_matches('/')
+        );
+        debug!("fetching {url}");
+
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call();
+        self.stamp()?;
+
+        let body = match response {
+            Ok(response) => response
+                .into_string()
+                .wrap_err_with(|| format!("can't read the response from {url}"))?,
+            Err(ureq::Error::Status(404, _)) => {
+                return Err(eyre!("{year} day {day} isn't unlocked yet ({url} is 404)"))
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                return Err(eyre!(
+                    "{url} answered {code}, is the session token still valid?"
+                ))
+            }
+            Err(e) => return Err(e).wrap_err_with(|| format!("can't get {url}")),
+        };
+
+        // inputs in data/ are stored without the trailing newline
+        let input = body.strip_suffix('\n').unwrap_or(&body);
+
+        let dir = path.parent().expect("input path has a day dir");
+        fs::create_dir_all(dir).wrap_err_with(|| format!("can't create {}", dir.display()))?;
+        fs::write(&path, input).wrap_err_with(|| format!("can't write {}", path.display()))?;
+
+        Ok(Fetched::Downloaded)
+    }
+
+    fn stamp_path(&self) -> PathBuf {
+        self.data_dir.join(STAMP_FILE)
+    }
+
+    fn wait_for_rate_limit(&self) -> color_eyre::Result<()> {
+        let last = match fs::read_to_string(self.stamp_path()) {
+            Ok(s) => s.trim().parse::<u64>().ok(),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e).wrap_err("can't read the last fetch time"),
+        };
+
+        if let Some(last) = last {
+            let next = Duration::from_millis(last) + self.opts.delay;
+            if let Some(wait) = next.checked_sub(now_since_epoch()) {
+                debug!("waiting {wait:.2?} before the next request");
+                thread::sleep(wait);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stamp(&self) -> color_eyre::Result<()> {
+        fs::create_dir_all(&self.data_dir)
+            .wrap_err_with(|| format!("can't create {}", self.data_dir.display()))?;
+        let now_ms = now_since_epoch().as_millis().to_string();
+        fs::write(self.stamp_path(), now_ms).wrap_err("can't record the last fetch time")
+    }
+}
+
+fn now_since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock is after 1970")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::fetch::{FetchOpts, Fetched, Fetcher};
+
+    // answers every request with `status` and `body`, returns the base URL and the request lines
+    fn mock_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            for line in BufReader::new(stream.try_clone().unwrap()).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+                request.push(line);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request
+        });
+
+        (base_url, handle)
+    }
+
+    fn temp_data_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2022-fetch-{name}-{}", std::process::id()));
+        drop(fs::remove_dir_all(&dir));
+        dir
+    }
+
+    #[test]
+    fn fetch_test() {
+        let (base_url, server) = mock_server("200 OK", "1\n2\n3\n");
+        let data_dir = temp_data_dir("ok");
+        let opts = FetchOpts {
+            base_url,
+            delay: Duration::ZERO,
+            force: false,
+        };
+        let fetcher = Fetcher::new("s3cr3t".to_string(), opts, &data_dir);
+
+        assert_eq!(fetcher.fetch(2023, 7).unwrap(), Fetched::Downloaded);
+        let request = server.join().unwrap();
+        assert_eq!(request[0], "GET /2023/day/7/input HTTP/1.1");
+        assert!(request.iter().any(|l| l == "Cookie: session=s3cr3t"));
+        assert_eq!(
+            fs::read_to_string(data_dir.join("2023/day07/input.txt")).unwrap(),
+            "1\n2\n3"
+        );
+
+        // the server is gone, so this only works from the cache
+        assert_eq!(fetcher.fetch(2023, 7).unwrap(), Fetched::Cached);
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_locked_test() {
+        let (base_url, server) = mock_server("404 Not Found", "");
+        let data_dir = temp_data_dir("locked");
+        let opts = FetchOpts {
+            base_url,
+            delay: Duration::ZERO,
+            force: false,
+        };
+        let fetcher = Fetcher::new("s3cr3t".to_string(), opts, &data_dir);
+
+        assert!(fetcher.fetch(2022, 25).is_err());
+        server.join().unwrap();
+        assert!(!data_dir.join("2022/day25/input.txt").exists());
+
+        drop(fs::remove_dir_all(&data_dir));
+    }
+}
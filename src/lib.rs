@@ -0,0 +1,75 @@
+// Advent of Code solvers, the `adventofcode2022` binary is a CLI over this
+
+use color_eyre::eyre::eyre;
+
+pub use crate::daylib::{Answer, Day, DayPart, Render, Rendered, Solver, Year};
+
+// day 16 with another start, time limit or number of agents than the puzzle's parts ask for
+pub use crate::day16::{max_release as max_valve_release, Plan as ValvePlan};
+
+// the bench subcommand's hook for splitting parse time from solve time, not part of the API
+#[doc(hidden)]
+pub use crate::daylib::take_parsed_at;
+
+mod daylib;
+pub mod shared;
+
+// `mod dayNN;` for every src/dayNN.rs and src/yYYYY/dayNN.rs, `pub const YEARS` and
+// `pub const LEGACY_YEAR`, see build.rs
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+pub fn year(number: u16) -> color_eyre::Result<&'static Year> {
+    YEARS
+        .iter()
+        .find(|y| y.number == number)
+        .ok_or_else(|| eyre!("no solvers for year {number}"))
+}
+
+pub fn solve(
+    year_number: u16,
+    day_number: u8,
+    part: DayPart,
+    input: &str,
+) -> color_eyre::Result<Answer> {
+    (year(year_number)?.day(day_number)?.solver(part))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{solve, Answer, DayPart, YEARS};
+
+    #[test]
+    fn registry_test() {
+        assert!(YEARS.windows(2).all(|w| w[0].number < w[1].number));
+        for year in &YEARS {
+            assert!(year.days.windows(2).all(|w| w[0].number < w[1].number));
+        }
+    }
+
+    #[test]
+    fn answer_test() {
+        assert_eq!(Answer::from(7_u32), Answer::Int(7));
+        assert_eq!(Answer::try_from(7_usize), Ok(Answer::Int(7)));
+        assert!(Answer::try_from(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn solve_test() {
+        assert_eq!(
+            solve(2022, 6, DayPart::First, "mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap(),
+            Answer::Int(7)
+        );
+        assert!(solve(2022, 26, DayPart::First, "").is_err());
+        assert!(solve(1999, 1, DayPart::First, "").is_err());
+
+        // input that parses but can't be solved is an error too, not a panic
+        assert!(solve(2022, 1, DayPart::First, "").is_err());
+        assert!(solve(2022, 3, DayPart::Second, "ab\ncd\nef").is_err());
+        assert!(solve(2022, 5, DayPart::First, "[A]\n 1 \n\nmove 1 from 0 to 1").is_err());
+        assert!(solve(2022, 7, DayPart::Second, "$ cd /\n$ ls\n80000000 a").is_err());
+        assert!(solve(2022, 8, DayPart::Second, "").is_err());
+        assert!(solve(2022, 14, DayPart::First, "498,4 -> 500,6").is_err());
+        assert!(solve(2022, 21, DayPart::First, "root: a / b\na: 3\nb: 2").is_err());
+        assert!(solve(2022, 21, DayPart::Second, "root: 5\nhumn: 1").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+
+use color_eyre::eyre::eyre;
+use log::{LevelFilter, Log, Metadata, Record};
+
+// solvers log through the `log` crate, this sends it all to stderr so stdout only has answers
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!("[{} {}] {}", record.level(), record.target(), record.args());
+        let uncaptured = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+            Some(lines) => {
+                lines.push(line);
+                None
+            }
+            None => Some(line),
+        });
+        if let Some(line) = uncaptured {
+            eprintln!("{line}");
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+// runs `f` keeping this thread's log lines aside, so parallel jobs don't interleave on stderr;
+// threads `f` spawns itself (day 16's par_bridge) still log straight away
+pub(crate) fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = CAPTURED.with(|captured| captured.replace(Some(vec![])));
+    let result = f();
+    let lines = CAPTURED.with(|captured| captured.replace(outer));
+
+    (result, lines.unwrap_or_default())
+}
+
+// logs lines `capture`d on another thread as if they were logged here
+pub(crate) fn forward(lines: Vec<String>) {
+    let uncaptured = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(captured_lines) => {
+            captured_lines.extend(lines);
+            vec![]
+        }
+        None => lines,
+    });
+    for line in uncaptured {
+        eprintln!("{line}");
+    }
+}
+
+pub(crate) fn init(level: LevelFilter) -> color_eyre::Result<()> {
+    log::set_logger(&LOGGER).map_err(|e| eyre!("can't set up logging: {e}"))?;
+    log::set_max_level(level);
+    Ok(())
+}
@@ -1,136 +1,515 @@
 extern crate core;
 
 use std::fs;
-use std::str::FromStr;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use bpaf::{construct, positional, OptionParser, Parser};
+use adventofcode2022::{DayPart, Rendered, ValvePlan, Year, LEGACY_YEAR};
+use bpaf::{construct, long, positional, short, OptionParser, Parser};
 use color_eyre::eyre::{eyre, WrapErr};
+use log::LevelFilter;
 
-use crate::daylib::Day;
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod daylib;
-mod shared;
+use crate::bench::BenchOpts;
+use crate::fetch::{FetchOpts, Fetched, Fetcher};
+use crate::runner::{DaySelection, Input, Job};
+
+mod answers;
+mod bench;
+mod fetch;
+mod logger;
+mod runner;
+mod scaffold;
 
 #[derive(Debug, Clone)]
-enum DayPart {
-    First,
-    Second,
+struct Cli {
+    log_level: LevelFilter,
+    threads: Option<usize>,
+    timeout: Option<Duration>,
+    year: u16,
+    opts: Opts,
 }
 
-impl FromStr for DayPart {
-    type Err = color_eyre::Report;
-
-    fn from_str(s: &str) -> color_eyre::Result<Self> {
-        use DayPart::{First, Second};
-
-        match s {
-            "1" => Ok(First),
-            "2" => Ok(Second),
-            other => Err(eyre!("expected 1 or 2, got {}", other)),
-        }
-    }
+#[derive(Debug, Clone)]
+enum Opts {
+    Run {
+        days: DaySelection,
+        part: Option<DayPart>,
+        input_flavour: Option<String>,
+        input_path: Option<PathBuf>,
+        render: Option<String>,
+        output: Option<PathBuf>,
+        plan: Option<ValvePlan>,
+    },
+    Check {
+        days: DaySelection,
+        input_flavour: Option<String>,
+    },
+    Bench {
+        bench_opts: BenchOpts,
+        json: Option<String>,
+        days: DaySelection,
+        part: Option<DayPart>,
+        input_flavour: Option<String>,
+        input_path: Option<PathBuf>,
+    },
+    Fetch {
+        fetch_opts: FetchOpts,
+        days: DaySelection,
+    },
+    New {
+        day: u8,
+    },
 }
 
-#[derive(Debug, Clone)]
-struct Opts {
-    day: u8,
-    part: DayPart,
-    input_flavour: Option<String>,
+fn input_path() -> impl Parser<Option<PathBuf>> {
+    short('i')
+        .long("input")
+        .help("Read the input from PATH instead of the data dir, - for stdin")
+        .argument::<PathBuf>("PATH")
+        .optional()
 }
 
-fn options() -> OptionParser<Opts> {
-    let day = positional::<u8>("day").help("Which day it is?");
-    let part = positional::<DayPart>("part").help("Which part it is? (1 or 2)");
-    let input_flavour = positional("input_flavour")
-        .help("Which input file to use (input_FLAVOUR.txt), uses input.txt if not set")
+fn options() -> OptionParser<Cli> {
+    let check = {
+        let days = positional::<DaySelection>("day")
+            .help("Which days to check? (a day, a range like 3..=9, or all)")
+            .fallback(DaySelection::all());
+        let input_flavour = positional("input_flavour")
+            .help("Which input to check (input_FLAVOUR.txt), checks every recorded one if not set")
+            .optional();
+
+        construct!(Opts::Check {
+            days,
+            input_flavour
+        })
+        .to_options()
+        .descr(
+            "Compare solver output with the answers in data/YYYY/dayNN/answer_FLAVOUR.txt \
+             (data/dayNN for 2022)",
+        )
+        .command("check")
+        .help("Check solvers against recorded answers")
+    };
+
+    let bench = {
+        let iterations = short('n')
+            .long("iterations")
+            .help("How many measured runs, 10 if not set")
+            .argument::<usize>("N")
+            .fallback(10)
+            .guard(|n| *n > 0, "need at least one run");
+        let warmup = short('w')
+            .long("warmup")
+            .help("How many runs to discard before measuring, 1 if not set")
+            .argument::<usize>("N")
+            .fallback(1);
+        let bench_opts = construct!(BenchOpts { iterations, warmup });
+        let json = long("json")
+            .help("Also write the results as JSON to PATH")
+            .argument::<String>("PATH")
+            .optional();
+        let days = positional::<DaySelection>("day")
+            .help("Which day to benchmark? (a day, a range like 3..=9, or all)");
+        let part = positional::<DayPart>("part")
+            .help("Which part it is? (1 or 2), runs both if not set")
+            .optional()
+            .catch();
+        let input_flavour = positional("input_flavour")
+            .help("Which input file to use (input_FLAVOUR.txt), uses input.txt if not set")
+            .optional();
+        let input_path = input_path();
+
+        construct!(Opts::Bench {
+            bench_opts,
+            json,
+            input_path,
+            days,
+            part,
+            input_flavour
+        })
+        .to_options()
+        .descr(
+            "Time solvers over several runs, separating parsing from solving where solvers mark it",
+        )
+        .command("bench")
+        .help("Benchmark solvers")
+    };
+
+    let fetch = {
+        let base_url = long("base-url")
+            .env("AOC_BASE_URL")
+            .help("Where to download inputs from")
+            .argument::<String>("URL")
+            .fallback(fetch::DEFAULT_BASE_URL.to_string());
+        let delay = long("delay")
+            .help("Seconds to wait between requests, 5 if not set")
+            .argument::<u64>("SECS")
+            .fallback(5)
+            .map(Duration::from_secs);
+        let force = short('f')
+            .long("force")
+            .help("Download even if the input is already there")
+            .switch();
+        let fetch_opts = construct!(FetchOpts {
+            base_url,
+            delay,
+            force
+        });
+        let days = positional::<DaySelection>("day")
+            .help("Which days to fetch? (a day, a range like 3..=9, or all)");
+
+        construct!(Opts::Fetch { fetch_opts, days })
+            .to_options()
+            .descr(
+                "Download puzzle inputs into data/YYYY/dayNN/input.txt (data/dayNN for 2022), \
+                 the session token comes from AOC_SESSION, .aoc_session or ~/.config/aoc/session",
+            )
+            .command("fetch")
+            .help("Download puzzle inputs")
+    };
+
+    let new = {
+        let day = positional::<u8>("day").help("Which day to create? (1 to 25)");
+
+        construct!(Opts::New { day })
+            .to_options()
+            .descr(
+                "Create src/dayNN.rs (src/yYYYY/dayNN.rs after 2022) from src/day_template.rs \
+                 and empty inputs in the day's data dir",
+            )
+            .command("new")
+            .help("Scaffold a new day")
+    };
+
+    let run = {
+        let days = positional::<DaySelection>("day")
+            .help("Which day it is? (a day, a range like 3..=9, or all)");
+        let part = positional::<DayPart>("part")
+            .help("Which part it is? (1 or 2), runs both if not set")
+            .optional()
+            // lets "10 test2" run both parts on a flavour
+            .catch();
+        let input_flavour = positional("input_flavour")
+            .help("Which input file to use (input_FLAVOUR.txt), uses input.txt if not set")
+            .optional();
+        let input_path = input_path();
+        let render = short('r')
+            .long("render")
+            .help("Print the day's NAME render instead of solving it, like day 12's path")
+            .argument::<String>("NAME")
+            .optional();
+        let output = short('o')
+            .long("output")
+            .help("Write the render to PATH, images are PNG for .png and PPM otherwise")
+            .argument::<PathBuf>("PATH")
+            .optional();
+        let plan = long("plan")
+            .help(
+                "Solve day 16 from START for MINUTES with AGENTS opening valves instead of \
+                 either part, part 2 is AA,26,2",
+            )
+            .argument::<ValvePlan>("START,MINUTES,AGENTS")
+            .optional();
+
+        construct!(Opts::Run {
+            input_path,
+            render,
+            output,
+            plan,
+            days,
+            part,
+            input_flavour
+        })
+    };
+
+    // warnings by default, solver progress with -v, grid dumps and search traces with -vv
+    let log_level = {
+        let quiet = short('q')
+            .long("quiet")
+            .help("Don't log anything, print bare answers")
+            .req_flag(LevelFilter::Off);
+        let verbose = short('v')
+            .long("verbose")
+            .help("Log what solvers are doing to stderr, repeat for more detail")
+            .req_flag(())
+            .many()
+            .map(|v| match v.len() {
+                0 => LevelFilter::Warn,
+                1 => LevelFilter::Debug,
+                _ => LevelFilter::Trace,
+            });
+        construct!([quiet, verbose])
+    };
+    let year = short('y')
+        .long("year")
+        .env("AOC_YEAR")
+        .help("Which year's puzzles, 2022 if not set")
+        .argument::<u16>("YEAR")
+        .fallback(LEGACY_YEAR);
+    let threads = short('j')
+        .long("jobs")
+        .help("How many solvers to run at once, one per CPU if not set")
+        .argument::<usize>("N")
+        .guard(|n| *n > 0, "need at least one thread")
+        .optional();
+    let timeout = short('t')
+        .long("timeout")
+        .help("Give up on a solver after SECS seconds, no limit if not set")
+        .argument::<u64>("SECS")
+        .guard(|secs| *secs > 0, "need at least a second")
+        .map(Duration::from_secs)
         .optional();
+    let opts = construct!([check, bench, fetch, new, run]);
 
-    construct!(Opts {
-        day,
-        part,
-        input_flavour
+    construct!(Cli {
+        log_level,
+        threads,
+        timeout,
+        year,
+        opts
     })
     .to_options()
-    .descr("Advent of Code 2022 solver")
+    .descr("Advent of Code solver")
 }
 
-const DAYS: [Day; 25] = [
-    day01::DAY,
-    day02::DAY,
-    day03::DAY,
-    day04::DAY,
-    day05::DAY,
-    day06::DAY,
-    day07::DAY,
-    day08::DAY,
-    day09::DAY,
-    day10::DAY,
-    day11::DAY,
-    day12::DAY,
-    day13::DAY,
-    day14::DAY,
-    day15::DAY,
-    day16::DAY,
-    day17::DAY,
-    day18::DAY,
-    day19::DAY,
-    day20::DAY,
-    day21::DAY,
-    day22::DAY,
-    day23::DAY,
-    day24::DAY,
-    day25::DAY,
-];
-
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
-    let opts = options().run();
+    let Cli {
+        log_level,
+        threads,
+        timeout,
+        year,
+        opts,
+    } = options().run();
+    logger::init(log_level)?;
+    if let Some(threads) = threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .wrap_err("can't set up the thread pool")?;
+    }
+    // fetch and new work before there are any solvers for the year
+    let solvers = || adventofcode2022::year(year);
 
-    let day_struct = DAYS
-        .iter()
-        .find(|d| d.number == opts.day)
-        .unwrap_or_else(|| panic!("unexpected day {}", opts.day));
+    match opts {
+        Opts::Run {
+            days,
+            part,
+            input_flavour,
+            input_path,
+            render: None,
+            output: None,
+            plan: None,
+        } => {
+            let input = Input::from_args(input_path, input_flavour)?;
+            let year = solvers()?;
+            run(
+                year,
+                &jobs(year, &days, part, &input)?,
+                timeout,
+                log_level == LevelFilter::Off,
+            )
+        }
+        Opts::Run {
+            render: None,
+            output: Some(_),
+            ..
+        } => Err(eyre!("--output is for --render")),
+        Opts::Run {
+            render: Some(_),
+            plan: Some(_),
+            ..
+        } => Err(eyre!("--plan and --render don't go together")),
+        Opts::Run {
+            part: Some(_),
+            plan: Some(_),
+            ..
+        } => Err(eyre!("--plan replaces the part")),
+        Opts::Run {
+            days,
+            part: None,
+            input_flavour,
+            input_path,
+            render: None,
+            output: None,
+            plan: Some(plan),
+        } => {
+            let input = Input::from_args(input_path, input_flavour)?;
+            let release = plan_release(solvers()?, &days, &input, plan)?;
+            if log_level == LevelFilter::Off {
+                println!("{release}");
+            } else {
+                println!("result:\n{release}");
+            }
+            Ok(())
+        }
+        Opts::Run {
+            days,
+            part: _,
+            input_flavour,
+            input_path,
+            render: Some(name),
+            output,
+            plan: None,
+        } => {
+            let input = Input::from_args(input_path, input_flavour)?;
+            render(solvers()?, &days, &name, &input, output.as_deref())
+        }
+        Opts::Check {
+            days,
+            input_flavour,
+        } => {
+            let failures = answers::check(solvers()?, &days, input_flavour.as_deref(), timeout)?;
+            if failures > 0 {
+                return Err(eyre!("{failures} checks failed"));
+            }
+            Ok(())
+        }
+        Opts::Bench {
+            bench_opts,
+            json,
+            days,
+            part,
+            input_flavour,
+            input_path,
+        } => {
+            let input = Input::from_args(input_path, input_flavour)?;
+            let year = solvers()?;
+            let results = jobs(year, &days, part, &input)?
+                .into_iter()
+                .map(|job| {
+                    let result = bench::bench(year, job, &bench_opts)?;
+                    bench::print(&result);
+                    Ok(result)
+                })
+                .collect::<color_eyre::Result<Vec<_>>>()?;
 
-    let solver = match opts.part {
-        DayPart::First => day_struct.part1,
-        DayPart::Second => day_struct.part2,
-    };
+            if let Some(path) = json {
+                fs::write(&path, bench::to_json(&results))
+                    .wrap_err_with(|| format!("can't write {path}"))?;
+            }
+            Ok(())
+        }
+        Opts::Fetch { fetch_opts, days } => fetch(&fetch_opts, year, &days),
+        Opts::New { day } => scaffold::new_day(year, day),
+    }
+}
 
-    let input_path = if let Some(flavour) = opts.input_flavour {
-        format!("data/day{}/input_{flavour}.txt", opts.day)
-    } else {
-        format!("data/day{}/input.txt", opts.day)
+fn fetch(opts: &FetchOpts, year: u16, days: &DaySelection) -> color_eyre::Result<()> {
+    let data_dir = Path::new("data");
+    // the session token is only needed once something has to be downloaded
+    let mut fetcher = None;
+
+    // inputs usually come before the solvers, so this goes by the calendar and not the registry
+    for day in (1..=25).filter(|d| days.contains(*d)) {
+        let path = fetch::input_path(data_dir, year, day);
+        if !opts.force && path.exists() {
+            println!("day {day}: cached at {}", path.display());
+            continue;
+        }
+
+        let fetcher = match &mut fetcher {
+            Some(fetcher) => fetcher,
+            None => fetcher.insert(Fetcher::new(
+                fetch::session_token()?,
+                opts.clone(),
+                data_dir,
+            )),
+        };
+        match fetcher.fetch(year, day)? {
+            Fetched::Downloaded => println!("day {day}: downloaded to {}", path.display()),
+            Fetched::Cached => println!("day {day}: cached at {}", path.display()),
+        }
+    }
+
+    Ok(())
+}
+
+fn jobs(
+    year: &Year,
+    days: &DaySelection,
+    part: Option<DayPart>,
+    input: &Input,
+) -> color_eyre::Result<Vec<Job>> {
+    let jobs = runner::jobs(year, days, part, input);
+
+    if jobs.is_empty() {
+        return Err(eyre!("no solvers registered for {days:?}"));
+    }
+    // one puzzle input can't be right for several days
+    if !matches!(input, Input::Data(_)) && jobs.iter().any(|j| j.day != jobs[0].day) {
+        return Err(eyre!("--input needs a single day, got {days:?}"));
+    }
+
+    Ok(jobs)
+}
+
+// renders don't care about the part, the first job is as good as any
+fn render(
+    year: &Year,
+    days: &DaySelection,
+    name: &str,
+    input: &Input,
+    output: Option<&Path>,
+) -> color_eyre::Result<()> {
+    let jobs = jobs(year, days, None, input)?;
+    let job = &jobs[0];
+    if jobs.iter().any(|j| j.day != job.day) {
+        return Err(eyre!("--render needs a single day, got {days:?}"));
+    }
+    let render = year.day(job.day)?.render(name)?.render;
+
+    let bytes = match render(&job.read_input()?)? {
+        Rendered::Text(text) => format!("{text}\n").into_bytes(),
+        Rendered::Image(image) => match output {
+            Some(path) if path.extension().map_or(false, |e| e == "png") => image.to_png(),
+            _ => image.to_ppm(),
+        },
     };
+    match output {
+        Some(path) => {
+            fs::write(path, bytes).wrap_err_with(|| format!("can't write {}", path.display()))
+        }
+        None => io::stdout()
+            .write_all(&bytes)
+            .wrap_err("can't write the render to stdout"),
+    }
+}
 
-    let input =
-        fs::read_to_string(&input_path).wrap_err(format!("input error at {}", &input_path))?;
-    println!("result:\n{}", solver(&input).wrap_err("solver error")?);
+// plans are day 16's, other days don't have valves
+fn plan_release(
+    year: &Year,
+    days: &DaySelection,
+    input: &Input,
+    plan: ValvePlan,
+) -> color_eyre::Result<u32> {
+    let jobs = jobs(year, days, Some(DayPart::First), input)?;
+    if year.number != LEGACY_YEAR || jobs.iter().any(|j| j.day != 16) {
+        return Err(eyre!("--plan is for {LEGACY_YEAR} day 16, got {days:?}"));
+    }
+    adventofcode2022::max_valve_release(&jobs[0].read_input()?, plan)
+}
+
+fn run(
+    year: &Year,
+    jobs: &[Job],
+    timeout: Option<Duration>,
+    quiet: bool,
+) -> color_eyre::Result<()> {
+    if let [job] = jobs {
+        // a single solver keeps the plain output and fails loudly
+        let result = runner::run(year, job.clone(), timeout);
+        let answer = result.outcome?;
+        if quiet {
+            println!("{answer}");
+        } else {
+            println!("result:\n{answer}");
+        }
+    } else {
+        runner::print_table(&runner::run_all(year, jobs, timeout)?);
+    }
 
     Ok(())
 }
@@ -139,3 +518,33 @@ fn main() -> color_eyre::Result<()> {
 fn check_options() {
     options().check_invariants(true);
 }
+
+#[test]
+fn plan_option_test() {
+    let parse = |args: &[&str]| {
+        options()
+            .run_inner(bpaf::Args::from(args))
+            .map(|cli| cli.opts)
+    };
+
+    assert!(matches!(
+        parse(&["--plan", "DD,20,3", "16", "test"]),
+        Ok(Opts::Run {
+            plan: Some(_),
+            part: None,
+            input_flavour: Some(_),
+            ..
+        })
+    ));
+    assert!(parse(&["--plan", "DD,20", "16"]).is_err());
+
+    // the sample's answers, from a plan instead of the part
+    let year = adventofcode2022::year(LEGACY_YEAR).unwrap();
+    let days = "16".parse().unwrap();
+    let input = Input::from_args(None, Some("test".to_string())).unwrap();
+    let release = |plan: &str| plan_release(year, &days, &input, plan.parse().unwrap());
+    assert_eq!(release("AA,30,1").unwrap(), 1651);
+    assert_eq!(release("AA,26,2").unwrap(), 1707);
+    let day_15 = "15".parse().unwrap();
+    assert!(plan_release(year, &day_15, &input, "AA,30,1".parse().unwrap()).is_err());
+}
@@ -0,0 +1,500 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::ops::RangeInclusive;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use adventofcode2022::{Answer, DayPart, Solver, Year, LEGACY_YEAR};
+use color_eyre::eyre::{eyre, WrapErr};
+use rayon::prelude::*;
+
+use crate::logger;
+
+// "all", a single day ("7") or an inclusive range ("3..=9")
+#[derive(Debug, Clone)]
+pub(crate) struct DaySelection(RangeInclusive<u8>);
+
+impl DaySelection {
+    pub(crate) fn all() -> Self {
+        DaySelection(u8::MIN..=u8::MAX)
+    }
+
+    pub(crate) fn contains(&self, day: u8) -> bool {
+        self.0.contains(&day)
+    }
+}
+
+impl FromStr for DaySelection {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> color_eyre::Result<Self> {
+        if s == "all" {
+            return Ok(DaySelection::all());
+        }
+
+        let range = if let Some((from_s, to_s)) = s.split_once("..=") {
+            from_s
+                .parse()
+                .wrap_err_with(|| format!("can't parse {from_s:?} into a day"))?
+                ..=to_s
+                    .parse()
+                    .wrap_err_with(|| format!("can't parse {to_s:?} into a day"))?
+        } else {
+            let day = s.parse().wrap_err_with(|| {
+                format!("expected all, a day or a range like 3..=9, got {s:?}")
+            })?;
+            day..=day
+        };
+
+        if range.is_empty() {
+            return Err(eyre!("empty day range {s:?}"));
+        }
+
+        Ok(DaySelection(range))
+    }
+}
+
+// where a job's puzzle input comes from
+#[derive(Debug, Clone)]
+pub(crate) enum Input {
+    // input_FLAVOUR.txt in the day's data dir, or input.txt without a flavour
+    Data(Option<String>),
+    File(PathBuf),
+    // read once up front, every job gets the same contents
+    Stdin(Arc<str>),
+}
+
+impl Input {
+    // `--input -` is stdin, any other `--input` is a path, otherwise it's a data flavour
+    pub(crate) fn from_args(
+        path: Option<PathBuf>,
+        flavour: Option<String>,
+    ) -> color_eyre::Result<Self> {
+        match (path, flavour) {
+            (Some(_), Some(flavour)) => Err(eyre!(
+                "got both --input and an input flavour ({flavour}), pick one"
+            )),
+            (Some(path), None) if path.as_os_str() == "-" => {
+                let mut s = String::new();
+                io::stdin()
+                    .read_to_string(&mut s)
+                    .wrap_err("can't read input from stdin")?;
+                Ok(Input::Stdin(s.into()))
+            }
+            (Some(path), None) => Ok(Input::File(path)),
+            (None, flavour) => Ok(Input::Data(flavour)),
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Data(None) => write!(f, "input"),
+            Input::Data(Some(flavour)) => write!(f, "{flavour}"),
+            Input::File(path) => write!(f, "{}", path.display()),
+            Input::Stdin(_) => write!(f, "stdin"),
+        }
+    }
+}
+
+// data/YYYY/dayNN, or data/dayNN for the legacy year when that's where the files already are
+pub(crate) fn day_dir(data_root: &Path, year: u16, day: u8) -> PathBuf {
+    let dir = data_root
+        .join(year.to_string())
+        .join(format!("day{day:02}"));
+    let legacy_dir = data_root.join(format!("day{day:02}"));
+
+    if year == LEGACY_YEAR && !dir.exists() && legacy_dir.exists() {
+        legacy_dir
+    } else {
+        dir
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Job {
+    pub(crate) year: u16,
+    pub(crate) day: u8,
+    pub(crate) part: DayPart,
+    pub(crate) input: Input,
+}
+
+impl Job {
+    pub(crate) fn read_input(&self) -> color_eyre::Result<String> {
+        let data_dir = || day_dir(Path::new("data"), self.year, self.day);
+        let path = match &self.input {
+            Input::Data(Some(flavour)) => data_dir().join(format!("input_{flavour}.txt")),
+            Input::Data(None) => data_dir().join("input.txt"),
+            Input::File(path) => path.clone(),
+            Input::Stdin(s) => return Ok(s.to_string()),
+        };
+        fs::read_to_string(&path).wrap_err_with(|| format!("input error at {}", path.display()))
+    }
+}
+
+// every registered day in the selection, both parts unless a part is given
+pub(crate) fn jobs(
+    year: &Year,
+    selection: &DaySelection,
+    part: Option<DayPart>,
+    input: &Input,
+) -> Vec<Job> {
+    let parts = part.map_or(DayPart::BOTH.to_vec(), |p| vec![p]);
+
+    year.days
+        .iter()
+        .filter(|d| selection.contains(d.number))
+        .flat_map(|d| {
+            parts.iter().map(|part| Job {
+                year: year.number,
+                day: d.number,
+                part: *part,
+                input: input.clone(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub(crate) struct JobResult {
+    pub(crate) job: Job,
+    // solver time only, reading the input isn't included
+    pub(crate) elapsed: Duration,
+    pub(crate) outcome: color_eyre::Result<Answer>,
+}
+
+// a solver that didn't return an answer or an error of its own
+#[derive(Debug)]
+pub(crate) enum SolverError {
+    Panicked {
+        day: u8,
+        part: DayPart,
+        message: String,
+        location: Option<String>,
+    },
+    TimedOut {
+        day: u8,
+        part: DayPart,
+        limit: Duration,
+    },
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::Panicked {
+                day,
+                part,
+                message,
+                location: Some(location),
+            } => write!(f, "day {day} part {part} panicked at {location}: {message}"),
+            SolverError::Panicked {
+                day,
+                part,
+                message,
+                location: None,
+            } => write!(f, "day {day} part {part} panicked: {message}"),
+            SolverError::TimedOut { day, part, limit } => {
+                write!(f, "day {day} part {part} timed out after {limit:.2?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+thread_local! {
+    // set on solver threads, their panics are reported as errors instead of by the panic hook
+    static IN_SOLVER: Cell<bool> = const { Cell::new(false) };
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// keeps the hook that was there (color-eyre's report) for panics outside of solvers
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let outer_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if IN_SOLVER.with(Cell::get) {
+                let location = info.location().map(ToString::to_string);
+                PANIC_LOCATION.with(|l| *l.borrow_mut() = location);
+            } else {
+                outer_hook(info);
+            }
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+// runs the solver on a thread of its own so a panic or a hang only takes down this job;
+// there's no stopping a thread, so a solver that runs out of time keeps going in the background
+fn solve(
+    solver: Solver,
+    input: String,
+    job: &Job,
+    timeout: Option<Duration>,
+) -> (Duration, color_eyre::Result<Answer>) {
+    install_panic_hook();
+
+    let (day, part) = (job.day, job.part);
+    let (sender, receiver) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name(format!("day {day} part {part}"))
+        .spawn(move || {
+            IN_SOLVER.with(|s| s.set(true));
+            let now = Instant::now();
+            let (result, log_lines) = logger::capture(|| panic::catch_unwind(|| solver(&input)));
+            let elapsed = now.elapsed();
+
+            let outcome = match result {
+                Ok(outcome) => outcome.wrap_err("solver error"),
+                Err(payload) => Err(SolverError::Panicked {
+                    day,
+                    part,
+                    message: panic_message(payload.as_ref()),
+                    location: PANIC_LOCATION.with(RefCell::take),
+                }
+                .into()),
+            };
+            // nobody is listening any more when the job timed out
+            sender.send((elapsed, outcome, log_lines)).ok();
+        });
+    if let Err(e) = spawned {
+        return (
+            Duration::ZERO,
+            Err(e).wrap_err("can't start a thread for the solver"),
+        );
+    }
+
+    let received = match timeout {
+        Some(limit) => receiver.recv_timeout(limit).map_err(|e| match e {
+            RecvTimeoutError::Timeout => SolverError::TimedOut { day, part, limit }.into(),
+            RecvTimeoutError::Disconnected => eyre!("solver thread died"),
+        }),
+        None => receiver.recv().map_err(|_| eyre!("solver thread died")),
+    };
+
+    match received {
+        Ok((elapsed, outcome, log_lines)) => {
+            logger::forward(log_lines);
+            (elapsed, outcome)
+        }
+        Err(e) => (timeout.unwrap_or_default(), Err(e)),
+    }
+}
+
+// `timeout` is wall-clock time for the solver alone
+pub(crate) fn run(year: &Year, job: Job, timeout: Option<Duration>) -> JobResult {
+    let mut elapsed = Duration::ZERO;
+
+    let outcome = year.day(job.day).and_then(|day| {
+        let input = job.read_input()?;
+
+        let (solver_elapsed, result) = solve(day.solver(job.part), input, &job, timeout);
+        elapsed = solver_elapsed;
+
+        result
+    });
+
+    JobResult {
+        job,
+        elapsed,
+        outcome,
+    }
+}
+
+// runs jobs across a rayon pool, results come back in the order of `jobs`;
+// each job's log lines are held back and printed together, also in job order
+pub(crate) fn run_all(
+    year: &Year,
+    jobs: &[Job],
+    timeout: Option<Duration>,
+) -> color_eyre::Result<Vec<JobResult>> {
+    // jobs spend their time blocked on their solver thread, so they get a pool of their own;
+    // on the global one they could take up every worker and starve solvers that use rayon
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(rayon::current_num_threads())
+        .thread_name(|i| format!("job runner {i}"))
+        .build()
+        .wrap_err("can't start the job runner threads")?;
+    let results = pool.install(|| {
+        jobs.par_iter()
+            .map(|job| logger::capture(|| run(year, job.clone(), timeout)))
+            .collect::<Vec<_>>()
+    });
+
+    Ok(results
+        .into_iter()
+        .map(|(result, log_lines)| {
+            for line in log_lines {
+                eprintln!("{line}");
+            }
+            result
+        })
+        .collect())
+}
+
+pub(crate) fn print_table(results: &[JobResult]) {
+    let rows = results
+        .iter()
+        .map(|r| {
+            let (answer, status) = match &r.outcome {
+                Ok(answer) => (answer.to_string(), "ok".to_string()),
+                // {:#} keeps the whole error chain on one line
+                Err(e) => (String::new(), format!("failed: {e:#}")),
+            };
+            (
+                r.job.day.to_string(),
+                r.job.part.to_string(),
+                answer,
+                format!("{:.2?}", r.elapsed),
+                status,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let answer_width = rows
+        .iter()
+        .flat_map(|(_, _, answer, _, _)| answer.lines())
+        .map(str::len)
+        .chain(["answer".len()])
+        .max()
+        .unwrap();
+    let time_width = rows
+        .iter()
+        .map(|(_, _, _, time, _)| time.len())
+        .chain(["time".len()])
+        .max()
+        .unwrap();
+
+    println!(
+        "{:>3}  {:>4}  {:<answer_width$}  {:>time_width$}  status",
+        "day", "part", "answer", "time"
+    );
+    println!(
+        "{}",
+        "-".repeat(3 + 2 + 4 + 2 + answer_width + 2 + time_width + 2 + 6)
+    );
+
+    for (day, part, answer, time, status) in &rows {
+        // multi-line answers (day 10's screen) continue on the following rows
+        let mut answer_lines = answer.lines();
+        println!(
+            "{day:>3}  {part:>4}  {:<answer_width$}  {time:>time_width$}  {status}",
+            answer_lines.next().unwrap_or_default()
+        );
+        for line in answer_lines {
+            println!("{:>3}  {:>4}  {line:<answer_width$}", "", "");
+        }
+    }
+
+    let failed = results.iter().filter(|r| r.outcome.is_err()).count();
+    let total_time = results.iter().map(|r| r.elapsed).sum::<Duration>();
+    println!(
+        "\n{} solvers, {failed} failed, {total_time:.2?} total",
+        results.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::RangeInclusive;
+    use std::thread;
+    use std::time::Duration;
+
+    use adventofcode2022::{Answer, Day, DayPart, Year};
+    use test_case::test_case;
+
+    use crate::runner::{run, DaySelection, Input, Job, SolverError};
+
+    fn panics(input: &str) -> color_eyre::Result<Answer> {
+        panic!("can't parse {input:?}")
+    }
+
+    fn hangs(_input: &str) -> color_eyre::Result<Answer> {
+        thread::sleep(Duration::from_secs(60));
+        Ok(0.into())
+    }
+
+    const YEAR: Year = Year {
+        number: 2022,
+        days: &[Day {
+            number: 1,
+            part1: panics,
+            part2: hangs,
+            renders: &[],
+        }],
+    };
+
+    fn job(part: DayPart) -> Job {
+        Job {
+            year: 2022,
+            day: 1,
+            part,
+            input: Input::Stdin("x".into()),
+        }
+    }
+
+    #[test]
+    fn run_panic_test() {
+        let error = run(&YEAR, job(DayPart::First), None).outcome.unwrap_err();
+
+        match error.downcast_ref::<SolverError>() {
+            Some(SolverError::Panicked {
+                day: 1,
+                part: DayPart::First,
+                message,
+                location: Some(location),
+            }) => {
+                assert_eq!(message, "can't parse \"x\"");
+                assert!(location.starts_with("src/runner.rs:"));
+            }
+            _ => panic!("expected a panic error, got {error:?}"),
+        }
+    }
+
+    #[test]
+    fn run_timeout_test() {
+        let timeout = Duration::from_millis(50);
+        let result = run(&YEAR, job(DayPart::Second), Some(timeout));
+
+        assert_eq!(result.elapsed, timeout);
+        assert!(matches!(
+            result.outcome.unwrap_err().downcast_ref::<SolverError>(),
+            Some(SolverError::TimedOut {
+                day: 1,
+                part: DayPart::Second,
+                ..
+            })
+        ));
+    }
+
+    #[test_case("7" => Some(7..=7))]
+    #[test_case("3..=9" => Some(3..=9))]
+    #[test_case("all" => Some(0..=255))]
+    #[test_case("9..=3" => None)]
+    #[test_case("3..9" => None)]
+    #[test_case("foo" => None)]
+    fn day_selection_tests(s: &str) -> Option<RangeInclusive<u8>> {
+        s.parse::<DaySelection>().ok().map(|selection| selection.0)
+    }
+}
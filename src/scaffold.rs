@@ -0,0 +1,62 @@
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use adventofcode2022::LEGACY_YEAR;
+use color_eyre::eyre::{eyre, WrapErr};
+
+use crate::runner;
+
+const TEMPLATE: &str = include_str!("day_template.rs");
+
+// src/dayNN.rs (src/yYYYY/dayNN.rs after 2022) from the template plus empty inputs
+// in the day's data dir, build.rs registers the module
+pub(crate) fn new_day(year: u16, day: u8) -> color_eyre::Result<()> {
+    if !(1..=25).contains(&day) {
+        return Err(eyre!("expected a day between 1 and 25, got {day}"));
+    }
+
+    let src_dir = if year == LEGACY_YEAR {
+        PathBuf::from("src")
+    } else {
+        Path::new("src").join(format!("y{year}"))
+    };
+    let src_path = src_dir.join(format!("day{day:02}.rs"));
+    if src_path.exists() {
+        return Err(eyre!("{} already exists", src_path.display()));
+    }
+    let source = fill_template(day)?;
+    fs::create_dir_all(&src_dir).wrap_err_with(|| format!("can't create {}", src_dir.display()))?;
+    fs::write(&src_path, source).wrap_err_with(|| format!("can't write {}", src_path.display()))?;
+    println!("created {}", src_path.display());
+
+    let data_dir = runner::day_dir(Path::new("data"), year, day);
+    fs::create_dir_all(&data_dir)
+        .wrap_err_with(|| format!("can't create {}", data_dir.display()))?;
+    for name in ["input.txt", "input_test.txt"] {
+        let path = data_dir.join(name);
+        // inputs might already be there from `fetch`
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => println!("created {}", path.display()),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => println!("kept {}", path.display()),
+            Err(e) => return Err(e).wrap_err_with(|| format!("can't create {}", path.display())),
+        }
+    }
+
+    Ok(())
+}
+
+fn fill_template(day: u8) -> color_eyre::Result<String> {
+    let placeholder = "number: todo!(),";
+    if !TEMPLATE.contains(placeholder) {
+        return Err(eyre!("day_template.rs has no {placeholder:?} to fill in"));
+    }
+    Ok(TEMPLATE.replace(placeholder, &format!("number: {day},")))
+}
+
+#[test]
+fn fill_template_test() {
+    let source = fill_template(7).unwrap();
+    assert!(source.contains("number: 7,"));
+    assert!(!source.contains("number: todo!()"));
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str::FromStr;
 
 use nom::{
@@ -5,19 +6,214 @@ use nom::{
     character::complete::digit1,
     combinator::{map_res, opt, recognize},
     sequence::preceded,
-    IResult,
+    Finish, IResult,
 };
 
-pub(crate) fn parse_unum<T: FromStr>(i: &str) -> IResult<&str, T> {
+mod cycle;
+mod grid;
+mod image;
+pub mod search;
+mod sparse;
+
+pub use cycle::{find_cycle, Cycle};
+pub use grid::{Grid, DELTAS4, DELTAS8};
+pub use image::{Image, Rgb};
+pub use sparse::{Point, SparseGrid};
+
+pub fn parse_unum<T: FromStr>(i: &str) -> IResult<&str, T> {
     let (i, number) = map_res(digit1, str::parse)(i)?;
 
     Ok((i, number))
 }
 
-pub(crate) fn parse_inum<T: FromStr>(i: &str) -> IResult<&str, T> {
+pub fn parse_inum<T: FromStr>(i: &str) -> IResult<&str, T> {
     let (i, number) = map_res(recognize(preceded(opt(tag("-")), digit1)), |s: &str| {
         s.parse()
     })(i)?;
 
     Ok((i, number))
 }
+
+// how much of a long line (day 17's jets) is shown on either side of the error
+const SNIPPET_CONTEXT: usize = 30;
+
+// malformed puzzle input, pointing at where in the input it went wrong:
+//
+//   line 2, column 24: can't parse "x" as a number
+//     | Valve BB has flow rate=x; tunnels lead to valves CC, AA
+//     |                        ^
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // both start at 1, the column counts chars
+    pub line: usize,
+    pub column: usize,
+    // the offending line, cut down around the column when it's long
+    pub snippet: String,
+    pub message: String,
+    // byte offset into the input and the column in `snippet`
+    offset: usize,
+    caret: usize,
+}
+
+// byte offset of `part` in `whole`, `part` has to be a slice of it
+fn offset_in(whole: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).wrapping_sub(whole.as_ptr() as usize);
+    assert!(
+        offset <= whole.len() && offset + part.len() <= whole.len(),
+        "{part:?} isn't a slice of the input"
+    );
+    offset
+}
+
+impl ParseError {
+    // an error at the start of `at`, which has to be a slice of `input`
+    pub fn new(input: &str, at: &str, message: impl Into<String>) -> Self {
+        let offset = offset_in(input, at);
+
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = input[line_start..]
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('\r');
+        let column = input[line_start..offset].chars().count() + 1;
+
+        let chars = line.chars().collect::<Vec<_>>();
+        let from = (column - 1).saturating_sub(SNIPPET_CONTEXT);
+        let to = (column - 1 + SNIPPET_CONTEXT).min(chars.len());
+        let mut snippet = chars[from.min(to)..to].iter().collect::<String>();
+        let mut caret = column - 1 - from;
+        if from > 0 {
+            snippet.insert_str(0, "...");
+            caret += 3;
+        }
+        if to < chars.len() {
+            snippet.push_str("...");
+        }
+
+        ParseError {
+            line: input[..line_start].matches('\n').count() + 1,
+            column,
+            snippet,
+            message: message.into(),
+            offset,
+            caret,
+        }
+    }
+
+    // an error built against `part` (a line, a block) moved to where `part` is in `input`
+    #[must_use]
+    pub fn within(self, input: &str, part: &str) -> Self {
+        let offset = offset_in(input, part) + self.offset;
+        ParseError::new(input, &input[offset..], self.message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, "  | {}", self.snippet)?;
+        write!(f, "  | {}^", " ".repeat(self.caret))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// `at` parsed with `FromStr`, errors point at `at` in `input`
+pub fn parse_at<T>(input: &str, at: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    at.parse()
+        .map_err(|e| ParseError::new(input, at, format!("can't parse {at:?}: {e}")))
+}
+
+// runs a nom parser over all of `input`, leftovers other than whitespace are an error too
+pub fn parse_all<'a, T>(
+    input: &'a str,
+    mut parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+) -> Result<T, ParseError> {
+    match parser(input).finish() {
+        Ok((rest, parsed)) if rest.trim().is_empty() => Ok(parsed),
+        Ok((rest, _)) => Err(ParseError::new(input, rest, "unexpected trailing input")),
+        Err(e) => Err(ParseError::new(
+            input,
+            e.input,
+            format!("unexpected input ({:?} failed)", e.code),
+        )),
+    }
+}
+
+// every line of `input` parsed by `parse`, its errors are moved to where the line is
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .map(|line| parse(line).map_err(|e| e.within(input, line)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::{parse_all, parse_at, parse_lines, parse_unum, ParseError};
+
+    #[test]
+    fn parse_error_test() {
+        let input = "1\n2\nthree\n4";
+        let error = parse_lines(input, |line| parse_at::<u8>(line, line)).unwrap_err();
+
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 1: can't parse \"three\": invalid digit found in string\n  \
+             | three\n  \
+             | ^"
+        );
+    }
+
+    #[test]
+    fn parse_error_within_test() {
+        let input = "a=1\nb=x";
+        let line = input.lines().nth(1).unwrap();
+        let error = parse_at::<u8>(line, &line[2..])
+            .unwrap_err()
+            .within(input, line);
+
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.snippet, "b=x");
+    }
+
+    #[test]
+    fn parse_error_snippet_test() {
+        let input = format!("{}x{}", "<".repeat(50), ">".repeat(50));
+        let error = ParseError::new(&input, &input[50..], "unexpected x");
+
+        assert_eq!(error.column, 51);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "line 1, column 51: unexpected x\n  | ...{}x{}...\n  | {}^",
+                "<".repeat(30),
+                ">".repeat(29),
+                " ".repeat(33)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_all_test() {
+        assert_eq!(parse_all("42", parse_unum::<u8>), Ok(42));
+        assert_eq!(parse_all("42\n", parse_unum::<u8>), Ok(42));
+        assert_eq!(parse_all("42x", parse_unum::<u8>).unwrap_err().column, 3);
+        assert_eq!(parse_all("x", parse_unum::<u8>).unwrap_err().column, 1);
+    }
+}
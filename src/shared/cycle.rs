@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// a simulation that settles into repeating itself after a while
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    // steps before the repeating part starts
+    pub prefix: usize,
+    // steps it takes to get back to the same state
+    pub period: usize,
+    // how much the value changes over one period
+    pub delta: i64,
+    // the value after 0, 1, ... steps up to the end of the first period
+    values: Vec<i64>,
+}
+
+impl Cycle {
+    // the value after any number of steps, without simulating past the first period
+    pub fn value_after(&self, steps: usize) -> i64 {
+        if steps < self.values.len() {
+            return self.values[steps];
+        }
+        let periods = (steps - self.prefix) / self.period;
+        let offset = (steps - self.prefix) % self.period;
+        self.values[self.prefix + offset] + i64::try_from(periods).unwrap() * self.delta
+    }
+}
+
+// steps `state` until it gets to one with a key it has seen before, `value` is whatever
+// should be extrapolated (like a height), the key has to capture everything that decides
+// how the state goes on, otherwise this finds cycles that aren't there.
+// doesn't return if the keys never repeat
+pub fn find_cycle<S, K: Eq + Hash>(
+    mut state: S,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    mut value: impl FnMut(&S) -> i64,
+) -> Cycle {
+    let mut seen = HashMap::new();
+    let mut values = Vec::new();
+
+    for steps in 0.. {
+        values.push(value(&state));
+        if let Some(prefix) = seen.insert(key(&state), steps) {
+            return Cycle {
+                prefix,
+                period: steps - prefix,
+                delta: values[steps] - values[prefix],
+                values,
+            };
+        }
+        step(&mut state);
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::cycle::find_cycle;
+
+    // counts up to 5 and then goes round 5, 6, 7, adding every position to a total
+    fn step((n, total): &mut (i64, i64)) {
+        *n = if *n < 5 { *n + 1 } else { 5 + (*n - 4) % 3 };
+        *total += *n;
+    }
+
+    #[test]
+    fn find_cycle_test() {
+        let cycle = find_cycle((0, 0), step, |(n, _)| *n, |(_, total)| *total);
+        assert_eq!((cycle.prefix, cycle.period, cycle.delta), (5, 3, 5 + 6 + 7));
+
+        let mut state = (0, 0);
+        for steps in 0..50 {
+            assert_eq!(cycle.value_after(steps), state.1, "after {steps} steps");
+            step(&mut state);
+        }
+    }
+}
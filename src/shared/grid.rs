@@ -0,0 +1,328 @@
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+
+use crate::shared::ParseError;
+
+// up, right, down, left
+pub const DELTAS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+// clockwise from the top left corner
+pub const DELTAS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+// a dense w x h grid stored row by row, positions are (x, y) with (0, 0) in the top left
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    w: usize,
+    h: usize,
+    d: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(w: usize, h: usize, fill: T) -> Self {
+        Grid {
+            w,
+            h,
+            d: vec![fill; w * h],
+        }
+    }
+
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        Grid {
+            w: self.h,
+            h: self.w,
+            d: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    // grows or shrinks the grid at the bottom, new rows are `fill`
+    pub fn resize_rows(&mut self, h: usize, fill: T) {
+        self.d.resize(h * self.w, fill);
+        self.h = h;
+    }
+
+    // one cell per char, every line has to be as long as the first one
+    pub fn parse(input: &str, cell: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        let w = input.lines().next().map_or(0, |l| l.chars().count());
+        Self::parse_lines(input, w, None, cell)
+    }
+
+    fn parse_lines(
+        input: &str,
+        w: usize,
+        fill: Option<T>,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut d = Vec::with_capacity(w * input.lines().count());
+        for line in input.lines() {
+            let mut len = 0;
+            for (i, c) in line.char_indices() {
+                if len == w {
+                    return Err(ParseError::new(
+                        input,
+                        &line[i..],
+                        format!("expected {w} columns"),
+                    ));
+                }
+                d.push(cell(c).ok_or_else(|| {
+                    ParseError::new(input, &line[i..], format!("unexpected {c:?}"))
+                })?);
+                len += 1;
+            }
+            match &fill {
+                Some(fill) => d.extend(std::iter::repeat(fill.clone()).take(w - len)),
+                None if len < w => {
+                    return Err(ParseError::new(
+                        input,
+                        &line[line.len()..],
+                        format!("expected {w} columns, got {len}"),
+                    ))
+                }
+                None => {}
+            }
+        }
+
+        Ok(Grid::from_vec(w, d))
+    }
+
+    // like `parse`, but short lines are padded with `fill` up to the longest one
+    pub fn parse_padded(
+        input: &str,
+        fill: T,
+        cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let w = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        Self::parse_lines(input, w, Some(fill), cell)
+    }
+}
+
+impl<T> Grid<T> {
+    // `d` is row by row, its length has to be a multiple of `w`
+    pub fn from_vec(w: usize, d: Vec<T>) -> Self {
+        assert!(
+            w > 0 && d.len() % w == 0 || d.is_empty(),
+            "{} cells can't make rows of {w}",
+            d.len()
+        );
+        Grid {
+            w,
+            h: d.len().checked_div(w).unwrap_or(0),
+            d,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.w && y < self.h
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&T> {
+        self.contains((x, y)).then(|| &self.d[y * self.w + x])
+    }
+
+    pub fn get_mut(&mut self, (x, y): (usize, usize)) -> Option<&mut T> {
+        if self.contains((x, y)) {
+            Some(&mut self.d[y * self.w + x])
+        } else {
+            None
+        }
+    }
+
+    // negative coordinates are just out of bounds
+    pub fn get_signed(&self, (x, y): (isize, isize)) -> Option<&T> {
+        self.get((usize::try_from(x).ok()?, usize::try_from(y).ok()?))
+    }
+
+    pub fn get_signed_mut(&mut self, (x, y): (isize, isize)) -> Option<&mut T> {
+        self.get_mut((usize::try_from(x).ok()?, usize::try_from(y).ok()?))
+    }
+
+    // `pos` moved by `delta` if that's still on the grid
+    pub fn offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(pos).then_some(pos)
+    }
+
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DELTAS4.iter().filter_map(move |d| self.offset(pos, *d))
+    }
+
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DELTAS8.iter().filter_map(move |d| self.offset(pos, *d))
+    }
+
+    // row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let w = self.w;
+        (0..self.h).flat_map(move |y| (0..w).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.d.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.d.iter_mut()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.d.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.d[y * self.w..(y + 1) * self.w]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.d[y * self.w..(y + 1) * self.w]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        // chunks_exact doesn't take 0, an empty grid has no rows either way
+        self.d.chunks_exact(self.w.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(x < self.w, "no column {x} in a grid {} wide", self.w);
+        self.d[x..].iter().step_by(self.w)
+    }
+
+    pub fn columns(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = impl DoubleEndedIterator<Item = &T> + ExactSizeIterator>
+           + ExactSizeIterator {
+        (0..self.w).map(|x| self.column(x))
+    }
+
+    pub fn remove_rows(&mut self, rows: Range<usize>) {
+        drop(self.d.drain(rows.start * self.w..rows.end * self.w));
+        self.h -= rows.len();
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            w: self.w,
+            h: self.h,
+            d: self.d.iter().map(f).collect(),
+        }
+    }
+
+    // one line per row, no trailing newline
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.w + 1) * self.h);
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                s.push('\n');
+            }
+            s.extend(row.iter().map(&mut f));
+        }
+        s
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        self.get((x, y))
+            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        self.get_mut((x, y))
+            .unwrap_or_else(|| panic!("out of bounds access at x {x} y {y}"))
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(|c| *c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::Grid;
+
+    // 0 1
+    // 2 3
+    // 4 5
+    fn grid() -> Grid<u8> {
+        Grid::from_vec(2, vec![0, 1, 2, 3, 4, 5])
+    }
+
+    #[test]
+    fn grid_index_test() {
+        let grid = grid();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid[(0, 1)], 2);
+        assert_eq!(grid[(1, 1)], 3);
+        assert_eq!(grid[(0, 2)], 4);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get_signed((1, 2)), Some(&5));
+        assert_eq!(grid.get_signed((-1, 0)), None);
+    }
+
+    #[test]
+    fn grid_rows_columns_test() {
+        let grid = grid();
+        assert_eq!(grid.row(1), &[2, 3]);
+        assert_eq!(grid.rows().next_back(), Some(&[4, 5][..]));
+        assert_eq!(grid.column(1).rev().collect::<Vec<_>>(), [&5, &3, &1]);
+        assert_eq!(grid.transpose(), Grid::from_vec(3, vec![0, 2, 4, 1, 3, 5]));
+    }
+
+    #[test]
+    fn grid_neighbours_test() {
+        let grid = grid();
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours4((1, 1)).collect::<Vec<_>>(),
+            [(1, 0), (1, 2), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8((0, 1)).count(), 5);
+    }
+
+    #[test]
+    fn grid_parse_test() {
+        let grid = Grid::parse("#.\n.#\n", |c| Some(c == '#')).unwrap();
+        assert_eq!(grid, Grid::from_vec(2, vec![true, false, false, true]));
+        assert_eq!(grid.render(|b| if *b { '#' } else { '.' }), "#.\n.#");
+
+        let error = Grid::parse("##\n#x", |c| (c == '#').then_some(c)).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        let error = Grid::parse("##\n#", Some).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        let error = Grid::parse("##\n###", Some).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+
+        let grid = Grid::parse_padded(" #\n#", ' ', Some).unwrap();
+        assert_eq!(grid.to_string(), " #\n# ");
+    }
+}
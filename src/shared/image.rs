@@ -0,0 +1,146 @@
+pub type Rgb = [u8; 3];
+
+// a w x h RGB picture, positions are (x, y) with (0, 0) in the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    w: usize,
+    h: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(w: usize, h: usize, fill: Rgb) -> Self {
+        Image {
+            w,
+            h,
+            pixels: vec![fill; w * h],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    // fills the `size` x `size` square with its top left corner at `(x, y)`, for drawing
+    // grid cells bigger than a pixel
+    pub fn fill_square(&mut self, (x, y): (usize, usize), size: usize, colour: Rgb) {
+        for row in y..(y + size).min(self.h) {
+            let start = row * self.w;
+            self.pixels[start + x.min(self.w)..start + (x + size).min(self.w)].fill(colour);
+        }
+    }
+
+    // binary PPM (P6), about the simplest format anything can open
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.w, self.h).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    // 8 bit RGB PNG, the image data goes into uncompressed deflate blocks so there's no
+    // need for a compression library, these pictures are small anyway
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend(u32::try_from(self.w).unwrap().to_be_bytes());
+        ihdr.extend(u32::try_from(self.h).unwrap().to_be_bytes());
+        // bit depth, colour type (RGB), compression, filter, interlace
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        // every row starts with its filter type, 0 is none
+        let mut raw = Vec::with_capacity((self.w * 3 + 1) * self.h);
+        for row in self.pixels.chunks_exact(self.w.max(1)).take(self.h) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend(u32::try_from(data.len()).unwrap().to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+// a zlib stream of deflate blocks that aren't compressed at all
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate with a 32K window and no dictionary, the check bits make it a multiple of 31
+    let mut out = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(usize::from(u16::MAX)).peekable();
+    if blocks.peek().is_none() {
+        // a final empty block
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(u8::from(blocks.peek().is_none()));
+        let len = u16::try_from(block.len()).unwrap();
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, b| {
+        (0..8).fold(crc ^ u32::from(*b), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1, 0), |(a, b), x| {
+        let a = (a + u32::from(*x)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::image::{adler32, crc32};
+    use crate::shared::Image;
+
+    #[test]
+    fn checksums_test() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn image_test() {
+        let mut image = Image::new(3, 2, [0, 0, 0]);
+        image.fill_square((1, 0), 4, [255, 0, 0]);
+
+        assert_eq!(
+            image.to_ppm(),
+            b"P6\n3 2\n255\n\0\0\0\xff\0\0\xff\0\0\0\0\0\xff\0\0\xff\0\0"
+        );
+
+        let png = image.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x03\0\0\0\x02"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    }
+}
@@ -0,0 +1,254 @@
+use std::cmp;
+use std::collections::{hash_map::Entry, HashMap, VecDeque};
+use std::hash::Hash;
+
+use binary_heap_plus as bhp;
+
+// a graph to find the cheapest way to a goal in
+pub trait Search {
+    type State: Clone + Eq + Hash;
+
+    // the states one move away and what each move costs
+    fn successors(&self, state: &Self::State) -> Vec<(Self::State, u32)>;
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    // a lower bound of the cost to the nearest goal for A*, 0 makes it Dijkstra
+    fn heuristic(&self, _state: &Self::State) -> u32 {
+        0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S> {
+    // from the start to the goal, both included
+    pub states: Vec<S>,
+    pub cost: u32,
+}
+
+impl<S> Path<S> {
+    pub fn goal(&self) -> &S {
+        self.states.last().expect("a path has at least the start")
+    }
+}
+
+// follows `parent` links back from the goal
+fn unwind<S: Clone>(goal: S, cost: u32, parent: impl Fn(&S) -> Option<S>) -> Path<S> {
+    let mut states = vec![goal];
+    while let Some(prev) = parent(states.last().unwrap()) {
+        states.push(prev);
+    }
+    states.reverse();
+    Path { states, cost }
+}
+
+// the fewest moves to a goal, move costs are ignored and the path's cost is the number of moves
+pub fn bfs<P: Search>(problem: &P, start: P::State) -> Option<Path<P::State>> {
+    let mut came_from = HashMap::from([(start.clone(), None)]);
+    let mut frontier = VecDeque::from([(start, 0)]);
+
+    while let Some((state, moves)) = frontier.pop_front() {
+        if problem.is_goal(&state) {
+            return Some(unwind(state, moves, |s| came_from[s].clone()));
+        }
+
+        for (next, _) in problem.successors(&state) {
+            if let Entry::Vacant(e) = came_from.entry(next.clone()) {
+                e.insert(Some(state.clone()));
+                frontier.push_back((next, moves + 1));
+            }
+        }
+    }
+
+    None
+}
+
+// every state reachable from `start` with the fewest moves to it, goals are ignored
+pub fn bfs_distances<P: Search>(problem: &P, start: P::State) -> HashMap<P::State, u32> {
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut frontier = VecDeque::from([(start, 0)]);
+
+    while let Some((state, moves)) = frontier.pop_front() {
+        for (next, _) in problem.successors(&state) {
+            if let Entry::Vacant(e) = distances.entry(next.clone()) {
+                e.insert(moves + 1);
+                frontier.push_back((next, moves + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+pub fn dijkstra<P: Search>(problem: &P, start: P::State) -> Option<Path<P::State>> {
+    best_first(problem, start, |_| 0)
+}
+
+pub fn a_star<P: Search>(problem: &P, start: P::State) -> Option<Path<P::State>> {
+    best_first(problem, start, |s| problem.heuristic(s))
+}
+
+fn best_first<P: Search>(
+    problem: &P,
+    start: P::State,
+    heuristic: impl Fn(&P::State) -> u32,
+) -> Option<Path<P::State>> {
+    // cheapest known cost of getting to a state and where we got to it from
+    let mut best = HashMap::from([(start.clone(), (0, None))]);
+
+    // full path guess, cost so far, state
+    let mut frontier =
+        bhp::BinaryHeap::new_by_key(|node: &(u32, u32, P::State)| cmp::Reverse(node.0));
+    frontier.push((heuristic(&start), 0, start));
+
+    while let Some((_, cost, state)) = frontier.pop() {
+        if cost > best[&state].0 {
+            // we've found a cheaper way here since this was pushed
+            continue;
+        }
+
+        if problem.is_goal(&state) {
+            return Some(unwind(state, cost, |s| best[s].1.clone()));
+        }
+
+        for (next, move_cost) in problem.successors(&state) {
+            let new_cost = cost + move_cost;
+            match best.entry(next.clone()) {
+                Entry::Occupied(e) if e.get().0 <= new_cost => continue,
+                Entry::Occupied(mut e) => {
+                    e.insert((new_cost, Some(state.clone())));
+                }
+                Entry::Vacant(e) => {
+                    e.insert((new_cost, Some(state.clone())));
+                }
+            }
+            frontier.push((new_cost + heuristic(&next), new_cost, next));
+        }
+    }
+
+    None
+}
+
+// a tree to find the best scoring state in, without looking into branches that can't beat
+// the best state found so far
+pub trait BranchAndBound {
+    type State: Clone;
+    type Score: Copy + Ord;
+
+    // pushes the states one move away onto `out`, the ones pushed last are tried first:
+    // good scores early make for tighter bounds
+    fn successors(&self, state: &Self::State, out: &mut Vec<Self::State>);
+
+    fn score(&self, state: &Self::State) -> Self::Score;
+
+    // the best score anything reachable from `state` (including itself) could have
+    fn bound(&self, state: &Self::State) -> Self::Score;
+}
+
+// depth first branch and bound, returns the best score and the states leading to it
+pub fn dfs_bound<P: BranchAndBound>(problem: &P, start: P::State) -> (P::Score, Vec<P::State>) {
+    let mut best = (problem.score(&start), vec![start.clone()]);
+
+    // the states from the start to the current one
+    let mut path = Vec::new();
+    // states to visit and how far from the start each of them is, successors get pushed
+    // straight onto `frontier` so the depths are kept next to it
+    let mut frontier = vec![start];
+    let mut depths = vec![0];
+
+    while let (Some(state), Some(depth)) = (frontier.pop(), depths.pop()) {
+        path.truncate(depth);
+        path.push(state);
+        let state = path.last().unwrap();
+
+        let score = problem.score(state);
+        if score > best.0 {
+            best = (score, path.clone());
+        }
+
+        if problem.bound(state) <= best.0 {
+            continue;
+        }
+
+        problem.successors(state, &mut frontier);
+        depths.resize(frontier.len(), depth + 1);
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::search::{
+        a_star, bfs, bfs_distances, dfs_bound, dijkstra, BranchAndBound, Search,
+    };
+
+    // a line of numbers 0..=10, a step costs 1 and a jump of 3 costs 5
+    struct Line(u32);
+
+    impl Search for Line {
+        type State = u32;
+
+        fn successors(&self, state: &u32) -> Vec<(u32, u32)> {
+            [(state + 1, 1), (state + 3, 5), (state.wrapping_sub(1), 1)]
+                .into_iter()
+                .filter(|(s, _)| *s <= 10)
+                .collect()
+        }
+
+        fn is_goal(&self, state: &u32) -> bool {
+            *state == self.0
+        }
+
+        fn heuristic(&self, state: &u32) -> u32 {
+            self.0.abs_diff(*state)
+        }
+    }
+
+    #[test]
+    fn search_test() {
+        let path = bfs(&Line(6), 0).unwrap();
+        assert_eq!((path.states, path.cost), (vec![0, 3, 6], 2));
+
+        let path = dijkstra(&Line(6), 0).unwrap();
+        assert_eq!((path.states.len(), path.cost), (7, 6));
+        assert_eq!(a_star(&Line(6), 0).unwrap(), path);
+
+        assert_eq!(bfs(&Line(11), 0), None);
+        assert_eq!(bfs_distances(&Line(11), 10).len(), 11);
+        assert_eq!(bfs_distances(&Line(11), 10)[&0], 10);
+    }
+
+    // picking digits for the largest sum under a limit
+    struct Digits;
+
+    impl BranchAndBound for Digits {
+        type State = Vec<u32>;
+        type Score = u32;
+
+        fn successors(&self, state: &Vec<u32>, out: &mut Vec<Vec<u32>>) {
+            out.extend(
+                (1..=9)
+                    .filter(|d| self.score(state) + d <= 20 && state.len() < 3)
+                    .map(|d| state.iter().copied().chain([d]).collect()),
+            );
+        }
+
+        fn score(&self, state: &Vec<u32>) -> u32 {
+            state.iter().sum()
+        }
+
+        fn bound(&self, state: &Vec<u32>) -> u32 {
+            20.min(self.score(state) + 9 * (3 - u32::try_from(state.len()).unwrap()))
+        }
+    }
+
+    #[test]
+    fn dfs_bound_test() {
+        let (best, path) = dfs_bound(&Digits, vec![]);
+        assert_eq!(best, 20);
+        assert_eq!(path.last().unwrap(), &[9, 9, 2]);
+    }
+}
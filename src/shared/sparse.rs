@@ -0,0 +1,248 @@
+use std::ops::{Index, IndexMut};
+
+// cells per axis in one chunk, a power of two so splitting points is shifting and masking
+const CHUNK_BITS: u32 = 4;
+const CHUNK: isize = 1 << CHUNK_BITS;
+
+// chunk 0 is never written to and stays all `fill`, directory entries without a chunk
+// point at it so reads don't have to check for them
+const NO_CHUNK: u32 = 0;
+
+// signed coordinates a `SparseGrid` can be indexed with, 2D points have z = 0
+pub trait Point: Copy + Eq {
+    // how many axes are used, chunks only extend along those
+    const DIMS: u32;
+
+    fn coords(self) -> [isize; 3];
+    fn from_coords(coords: [isize; 3]) -> Self;
+}
+
+impl Point for (isize, isize) {
+    const DIMS: u32 = 2;
+
+    fn coords(self) -> [isize; 3] {
+        [self.0, self.1, 0]
+    }
+
+    fn from_coords([x, y, _]: [isize; 3]) -> Self {
+        (x, y)
+    }
+}
+
+impl Point for (isize, isize, isize) {
+    const DIMS: u32 = 3;
+
+    fn coords(self) -> [isize; 3] {
+        [self.0, self.1, self.2]
+    }
+
+    fn from_coords([x, y, z]: [isize; 3]) -> Self {
+        (x, y, z)
+    }
+}
+
+// the chunk a point is in and its index inside the chunk, x goes fastest
+#[allow(clippy::cast_sign_loss)]
+fn split(coords: [isize; 3]) -> ([isize; 3], usize) {
+    let [x, y, z] = coords.map(|c| c & (CHUNK - 1));
+    (
+        coords.map(|c| c >> CHUNK_BITS),
+        ((((z << CHUNK_BITS) + y) << CHUNK_BITS) + x) as usize,
+    )
+}
+
+// an unbounded grid that only allocates the chunks that were written to,
+// everything else reads as `fill`.
+// chunks are found through a directory with an entry for every chunk in the bounding box
+// of the allocated ones, that's one u32 for every 16x16 (or 16x16x16) cells and it saves
+// hashing chunk keys on every access. points written far apart still pay for the directory
+// entries between them
+#[derive(Debug, Clone)]
+pub struct SparseGrid<P, T> {
+    // chunk coordinates of directory entry 0 and how many chunks it spans on every axis
+    dir_min: [isize; 3],
+    dir_size: [usize; 3],
+    // chunk numbers, x goes fastest
+    dir: Vec<u32>,
+    // the cells of every chunk, chunk after chunk
+    cells: Vec<T>,
+    fill: T,
+    // inclusive, of every cell written to
+    bounds: Option<(P, P)>,
+}
+
+impl<P: Point, T: Clone> SparseGrid<P, T> {
+    #[allow(clippy::cast_sign_loss)]
+    const CHUNK_LEN: usize = (CHUNK as usize).pow(P::DIMS);
+
+    pub fn new(fill: T) -> Self {
+        SparseGrid {
+            dir_min: [0; 3],
+            dir_size: [0; 3],
+            dir: Vec::new(),
+            cells: vec![fill.clone(); Self::CHUNK_LEN],
+            fill,
+            bounds: None,
+        }
+    }
+
+    pub fn get(&self, p: P) -> &T {
+        let (chunk, i) = split(p.coords());
+        match self.dir_index(chunk) {
+            Some(d) => &self.cells[self.dir[d] as usize * Self::CHUNK_LEN + i],
+            None => &self.fill,
+        }
+    }
+
+    // allocates the chunk and grows the bounding box, every write goes through here
+    pub fn get_mut(&mut self, p: P) -> &mut T {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                P::from_coords(zip_with(min.coords(), p.coords(), isize::min)),
+                P::from_coords(zip_with(max.coords(), p.coords(), isize::max)),
+            ),
+            None => (p, p),
+        });
+
+        let (chunk, i) = split(p.coords());
+        let d = match self.dir_index(chunk) {
+            Some(d) => d,
+            None => {
+                self.grow_dir(chunk);
+                self.dir_index(chunk).unwrap()
+            }
+        };
+        if self.dir[d] == NO_CHUNK {
+            let c = self.cells.len() / Self::CHUNK_LEN;
+            self.dir[d] = u32::try_from(c).expect("too many chunks");
+            let len = self.cells.len() + Self::CHUNK_LEN;
+            self.cells.resize(len, self.fill.clone());
+        }
+        &mut self.cells[self.dir[d] as usize * Self::CHUNK_LEN + i]
+    }
+
+    // smallest and largest coordinates written to, inclusive
+    pub fn bounds(&self) -> Option<(P, P)> {
+        self.bounds
+    }
+
+    // every allocated cell, including the ones never written to
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells[Self::CHUNK_LEN..].iter()
+    }
+
+    fn dir_index(&self, chunk: [isize; 3]) -> Option<usize> {
+        let mut d = 0;
+        // z is always 0 in 2D, there's no need to check it
+        for axis in (0..P::DIMS as usize).rev() {
+            // a chunk before `dir_min` wraps around to a huge offset
+            #[allow(clippy::cast_sign_loss)]
+            let offset = chunk[axis].wrapping_sub(self.dir_min[axis]) as usize;
+            if offset >= self.dir_size[axis] {
+                return None;
+            }
+            d = d * self.dir_size[axis] + offset;
+        }
+        Some(d)
+    }
+
+    // makes room for `chunk`, at least doubling the directory on the axes it's outside of
+    // so growing it as cells get written takes amortised constant time
+    #[allow(clippy::cast_possible_wrap)]
+    fn grow_dir(&mut self, chunk: [isize; 3]) {
+        let (old_min, old_size) = (self.dir_min, self.dir_size);
+        let (mut min, mut size) = (old_min, old_size);
+        if self.dir.is_empty() {
+            (min, size) = (chunk, [1; 3]);
+        } else {
+            for axis in 0..3 {
+                let len = old_size[axis] as isize;
+                let max = old_min[axis] + len - 1;
+                let (lo, hi) = if chunk[axis] < old_min[axis] {
+                    (chunk[axis].min(old_min[axis] - len), max)
+                } else if chunk[axis] > max {
+                    (old_min[axis], chunk[axis].max(max + len))
+                } else {
+                    (old_min[axis], max)
+                };
+                min[axis] = lo;
+                size[axis] = usize::try_from(hi - lo + 1).unwrap();
+            }
+        }
+
+        let old_dir = std::mem::replace(&mut self.dir, vec![NO_CHUNK; size.iter().product()]);
+        (self.dir_min, self.dir_size) = (min, size);
+        for (d, c) in old_dir.into_iter().enumerate() {
+            if c != NO_CHUNK {
+                let x = d % old_size[0];
+                let y = d / old_size[0] % old_size[1];
+                let z = d / old_size[0] / old_size[1];
+                let coords = zip_with(old_min, [x, y, z].map(|c| c as isize), |m, c| m + c);
+                let new_d = self.dir_index(coords).unwrap();
+                self.dir[new_d] = c;
+            }
+        }
+    }
+}
+
+fn zip_with(a: [isize; 3], b: [isize; 3], f: impl Fn(isize, isize) -> isize) -> [isize; 3] {
+    [f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2])]
+}
+
+impl<P: Point, T: Clone> Index<P> for SparseGrid<P, T> {
+    type Output = T;
+
+    fn index(&self, p: P) -> &Self::Output {
+        self.get(p)
+    }
+}
+
+impl<P: Point, T: Clone> IndexMut<P> for SparseGrid<P, T> {
+    fn index_mut(&mut self, p: P) -> &mut Self::Output {
+        self.get_mut(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::sparse::split;
+    use crate::shared::SparseGrid;
+
+    #[test]
+    fn sparse_grid_test() {
+        let mut grid = SparseGrid::new('.');
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.values().count(), 0);
+
+        grid[(-100, 3)] = '#';
+        grid[(2000, -1)] = '#';
+        assert_eq!(grid[(-100, 3)], '#');
+        assert_eq!(grid[(2000, -1)], '#');
+        assert_eq!(grid[(-99, 3)], '.');
+        assert_eq!(grid[(5, 5)], '.');
+        assert_eq!(grid[(-5000, 5000)], '.');
+        assert_eq!(grid.bounds(), Some(((-100, -1), (2000, 3))));
+        assert_eq!(grid.values().filter(|c| **c == '#').count(), 2);
+
+        // every write counts, even one that leaves the fill there
+        grid[(10, 50)] = '.';
+        assert_eq!(grid.bounds(), Some(((-100, -1), (2000, 50))));
+
+        let mut cube = SparseGrid::new(0);
+        for i in -40..40 {
+            cube[(i, -i, i * 3)] = i;
+        }
+        assert!((-40..40).all(|i| cube[(i, -i, i * 3)] == i));
+        assert_eq!(cube.bounds(), Some(((-40, -39, -120), (39, 40, 117))));
+    }
+
+    #[test]
+    fn split_test() {
+        assert_eq!(split([0, 0, 0]), ([0, 0, 0], 0));
+        assert_eq!(split([-1, 0, 0]), ([-1, 0, 0], 15));
+        assert_eq!(split([17, 1, 0]), ([1, 0, 0], 17));
+        assert_eq!(split([-1, -1, -1]), ([-1, -1, -1], 4095));
+    }
+}